@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::algorithm::grid::Grid;
+
+/// Per-cell distance from the cell center to the nearest wall, in world units.
+#[derive(Debug, Clone)]
+pub struct ClearanceMap {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    data: Vec<f32>,
+}
+
+impl ClearanceMap {
+    /// Samples the grid's exact distance field at every cell center.
+    pub fn from_grid(grid: &Grid) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let cell_size = grid.cell_size();
+        let root = grid.root();
+        let field = grid.distance_field();
+        let data = (0..width * height)
+            .map(|i| {
+                let center = root + (Vec2::new((i % width) as f32, (i / width) as f32) + 0.5) * cell_size;
+                field.distance_at(center)
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            cell_size,
+            root,
+            data,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.data[y * self.width + x])
+    }
+
+    pub fn at(&self, pos: Vec2) -> f32 {
+        let local = (pos - self.root) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return 0.0;
+        }
+        self.get(local.x as usize, local.y as usize).unwrap_or(0.0)
+    }
+}
+
+/// Extra cost for passing closer to walls than `margin`, scaled by `weight`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClearanceCost {
+    pub weight: f32,
+    pub margin: f32,
+}
+
+impl Default for ClearanceCost {
+    fn default() -> Self {
+        Self {
+            weight: 0.0,
+            margin: 40.0,
+        }
+    }
+}
+
+impl ClearanceCost {
+    pub fn is_enabled(&self) -> bool {
+        self.weight > 0.0 && self.margin > 0.0
+    }
+
+    /// Zero once `clearance` reaches the margin, `weight` when touching a wall.
+    pub fn penalty(&self, clearance: f32) -> f32 {
+        if !self.is_enabled() {
+            return 0.0;
+        }
+        self.weight * (1.0 - clearance / self.margin).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,422 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::algorithm::clearance::ClearanceCost;
+use crate::algorithm::objective::ObjectiveWeights;
+use crate::algorithm::solve::{
+    a_star::AStarStrategy,
+    genetic::GeneticStrategy,
+    hpa::HpaStrategy,
+    hybrid::{HybridParam, HybridStrategy, PheromonePolicy},
+    prm::PrmStrategy,
+    rrt::RrtStrategy,
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AStarConfig {
+    pub step_size: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RrtConfig {
+    pub max_iterations: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PrmConfig {
+    pub samples: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GeneticConfig {
+    pub population_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HpaConfig {
+    pub cluster_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ClearanceConfig {
+    pub weight: f32,
+    pub margin: f32,
+}
+
+/// Weights of the hybrid's path cost; left-out weights are zero, except `length` which is 1.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ObjectiveConfig {
+    pub length: f32,
+    pub turns: f32,
+    pub turning_angle: f32,
+    pub clearance: f32,
+    pub clearance_margin: f32,
+    pub terrain: f32,
+}
+
+impl Default for ObjectiveConfig {
+    fn default() -> Self {
+        let weights = ObjectiveWeights::default();
+        Self {
+            length: weights.length,
+            turns: weights.turns,
+            turning_angle: weights.turning_angle,
+            clearance: weights.clearance,
+            clearance_margin: weights.clearance_margin,
+            terrain: weights.terrain,
+        }
+    }
+}
+
+/// What the hybrid keeps of its pheromone when the start or goal moves; see `PheromonePolicy`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum PheromonePolicyConfig {
+    Keep,
+    Reset,
+    Decay(f64),
+    GoalIndependent,
+}
+
+/// One named set of solver parameters. Hybrid parameters are keyed by
+/// `HybridParam::name`; anything left out takes the value the solvers started with.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SolverPreset {
+    pub seed: Option<u64>,
+    pub a_star: AStarConfig,
+    pub hybrid: BTreeMap<String, f64>,
+    /// Past iterations the hybrid keeps for the trail replay; 0 stops recording.
+    pub tour_history_size: Option<usize>,
+    pub rrt: RrtConfig,
+    pub prm: PrmConfig,
+    pub genetic: GeneticConfig,
+    pub hpa: HpaConfig,
+    pub clearance: Option<ClearanceConfig>,
+    pub objective: Option<ObjectiveConfig>,
+    pub pheromone_policy: Option<PheromonePolicyConfig>,
+    pub agent_radius: Option<f32>,
+    pub geodesic_heuristic: Option<bool>,
+    pub pareto: Option<bool>,
+    pub warm_start: Option<bool>,
+}
+
+/// The solvers and problem settings a preset is applied to.
+pub struct PresetTarget<'a> {
+    pub astar: &'a mut AStarStrategy,
+    pub hybrid: &'a mut HybridStrategy,
+    pub rrt: &'a mut RrtStrategy,
+    pub prm: &'a mut PrmStrategy,
+    pub genetic: &'a mut GeneticStrategy,
+    pub hpa: &'a mut HpaStrategy,
+    pub agent_radius: &'a mut f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SolverConfig {
+    pub active: String,
+    pub presets: BTreeMap<String, SolverPreset>,
+}
+
+impl SolverConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: SolverConfig = ron::from_str(text).map_err(|e| e.to_string())?;
+        if !config.presets.contains_key(&config.active) {
+            return Err(format!("active preset \"{}\" is not defined", config.active));
+        }
+        for (name, preset) in config.presets.iter() {
+            preset
+                .validate()
+                .map_err(|e| format!("preset \"{}\": {}", name, e))?;
+        }
+        Ok(config)
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&SolverPreset> {
+        self.presets.get(name)
+    }
+
+    /// Name of the preset after `name`, wrapping around to the first one.
+    pub fn next_preset_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.presets
+            .keys()
+            .skip_while(|x| x.as_str() != name)
+            .nth(1)
+            .or_else(|| self.presets.keys().next())
+            .map_or(name, |x| x.as_str())
+    }
+}
+
+impl SolverPreset {
+    /// A preset holding every setting `target` currently has, so it can serve as the base of others.
+    pub fn capture(target: &PresetTarget) -> Self {
+        let hybrid = &*target.hybrid;
+        let objective = hybrid.objective;
+        Self {
+            seed: hybrid.seed(),
+            a_star: AStarConfig {
+                step_size: Some(target.astar.step_size),
+            },
+            hybrid: HybridParam::iter()
+                .map(|x| (x.name().to_string(), hybrid.get_param(*x)))
+                .collect(),
+            tour_history_size: Some(hybrid.tour_history_size),
+            rrt: RrtConfig {
+                max_iterations: Some(target.rrt.max_iterations),
+            },
+            prm: PrmConfig {
+                samples: Some(target.prm.samples),
+            },
+            genetic: GeneticConfig {
+                population_size: Some(target.genetic.population_size),
+            },
+            hpa: HpaConfig {
+                cluster_size: Some(target.hpa.cluster_size),
+            },
+            clearance: Some(ClearanceConfig {
+                weight: target.astar.clearance_cost.weight,
+                margin: target.astar.clearance_cost.margin,
+            }),
+            objective: Some(ObjectiveConfig {
+                length: objective.length,
+                turns: objective.turns,
+                turning_angle: objective.turning_angle,
+                clearance: objective.clearance,
+                clearance_margin: objective.clearance_margin,
+                terrain: objective.terrain,
+            }),
+            pheromone_policy: Some(match hybrid.pheromone_policy() {
+                PheromonePolicy::Keep => PheromonePolicyConfig::Keep,
+                PheromonePolicy::Reset => PheromonePolicyConfig::Reset,
+                PheromonePolicy::Decay(factor) => PheromonePolicyConfig::Decay(factor),
+                PheromonePolicy::GoalIndependent => PheromonePolicyConfig::GoalIndependent,
+            }),
+            agent_radius: Some(*target.agent_radius),
+            geodesic_heuristic: Some(hybrid.geodesic_heuristic),
+            pareto: Some(hybrid.pareto),
+            warm_start: Some(hybrid.warm_start),
+        }
+    }
+
+    /// This preset with everything it leaves out taken from `base`. The seed is not inherited.
+    pub fn over(&self, base: &SolverPreset) -> SolverPreset {
+        let mut hybrid = base.hybrid.clone();
+        hybrid.extend(self.hybrid.iter().map(|(k, v)| (k.clone(), *v)));
+        SolverPreset {
+            seed: self.seed,
+            a_star: AStarConfig {
+                step_size: self.a_star.step_size.or(base.a_star.step_size),
+            },
+            hybrid,
+            tour_history_size: self.tour_history_size.or(base.tour_history_size),
+            rrt: RrtConfig {
+                max_iterations: self.rrt.max_iterations.or(base.rrt.max_iterations),
+            },
+            prm: PrmConfig {
+                samples: self.prm.samples.or(base.prm.samples),
+            },
+            genetic: GeneticConfig {
+                population_size: self.genetic.population_size.or(base.genetic.population_size),
+            },
+            hpa: HpaConfig {
+                cluster_size: self.hpa.cluster_size.or(base.hpa.cluster_size),
+            },
+            clearance: self.clearance.or(base.clearance),
+            objective: self.objective.or(base.objective),
+            pheromone_policy: self.pheromone_policy.or(base.pheromone_policy),
+            agent_radius: self.agent_radius.or(base.agent_radius),
+            geodesic_heuristic: self.geodesic_heuristic.or(base.geodesic_heuristic),
+            pareto: self.pareto.or(base.pareto),
+            warm_start: self.warm_start.or(base.warm_start),
+        }
+    }
+
+    /// Checks every section, hybrid entries against the ranges of `HybridParam`.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(name) = self.hybrid.keys().find(|x| HybridParam::from_name(x).is_none()) {
+            return Err(format!("unknown hybrid parameter \"{}\"", name));
+        }
+        if let Some(step_size) = self.a_star.step_size.filter(|x| *x <= 0.0) {
+            return Err(format!("a_star step_size must be positive, got {}", step_size));
+        }
+
+        if let Some(clearance) = self.clearance.filter(|x| x.weight < 0.0 || x.margin <= 0.0) {
+            return Err(format!(
+                "clearance needs weight >= 0 and margin > 0, got {:?}",
+                clearance
+            ));
+        }
+
+        for (name, value, min) in [
+            ("rrt.max_iterations", self.rrt.max_iterations, 1),
+            ("prm.samples", self.prm.samples, 2),
+            ("genetic.population_size", self.genetic.population_size, 2),
+            ("hpa.cluster_size", self.hpa.cluster_size, 1),
+        ] {
+            if let Some(value) = value.filter(|x| *x < min) {
+                return Err(format!("{} must be at least {}, got {}", name, min, value));
+            }
+        }
+
+        if let Some(objective) = self.objective {
+            let weights = [
+                objective.length,
+                objective.turns,
+                objective.turning_angle,
+                objective.clearance,
+                objective.clearance_margin,
+                objective.terrain,
+            ];
+            if weights.iter().any(|x| *x < 0.0) {
+                return Err(format!("objective weights must be >= 0, got {:?}", objective));
+            }
+        }
+
+        if let Some(PheromonePolicyConfig::Decay(factor)) = self.pheromone_policy
+            && !(0.0..=1.0).contains(&factor)
+        {
+            return Err(format!("pheromone decay factor must be within 0..=1, got {}", factor));
+        }
+
+        if let Some(radius) = self.agent_radius.filter(|x| *x < 0.0) {
+            return Err(format!("agent_radius must be >= 0, got {}", radius));
+        }
+
+        for (param, value) in self.hybrid_values() {
+            let (min, max) = param.range();
+            if !(min..=max).contains(&value) {
+                return Err(format!(
+                    "{} = {} is outside {}..={}",
+                    param.name(),
+                    value,
+                    min,
+                    max
+                ));
+            }
+        }
+
+        for (min_param, max_param) in [
+            (HybridParam::InitAlphaMin, HybridParam::InitAlphaMax),
+            (HybridParam::InitBetaMin, HybridParam::InitBetaMax),
+        ] {
+            if let (Some(min), Some(max)) = (
+                self.hybrid.get(min_param.name()),
+                self.hybrid.get(max_param.name()),
+            ) && min >= max
+            {
+                return Err(format!(
+                    "{} must be below {}",
+                    min_param.name(),
+                    max_param.name()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The known hybrid entries in declaration order.
+    fn hybrid_values(&self) -> Vec<(HybridParam, f64)> {
+        HybridParam::iter()
+            .filter_map(|x| Some((*x, *self.hybrid.get(x.name())?)))
+            .collect()
+    }
+
+    pub fn apply(&self, target: &mut PresetTarget) -> Result<(), String> {
+        self.validate()?;
+        let PresetTarget {
+            astar,
+            hybrid,
+            rrt,
+            prm,
+            genetic,
+            hpa,
+            agent_radius,
+        } = target;
+
+        if let Some(step_size) = self.a_star.step_size {
+            astar.step_size = step_size;
+        }
+        if let Some(max_iterations) = self.rrt.max_iterations {
+            rrt.max_iterations = max_iterations;
+        }
+        if let Some(samples) = self.prm.samples {
+            prm.samples = samples;
+        }
+        if let Some(population_size) = self.genetic.population_size {
+            genetic.population_size = population_size;
+        }
+        if let Some(cluster_size) = self.hpa.cluster_size {
+            hpa.cluster_size = cluster_size;
+        }
+        if let Some(tour_history_size) = self.tour_history_size {
+            hybrid.tour_history_size = tour_history_size;
+        }
+        if let Some(radius) = self.agent_radius {
+            **agent_radius = radius;
+        }
+        if let Some(geodesic_heuristic) = self.geodesic_heuristic {
+            hybrid.geodesic_heuristic = geodesic_heuristic;
+        }
+        if let Some(pareto) = self.pareto {
+            hybrid.pareto = pareto;
+        }
+        if let Some(warm_start) = self.warm_start {
+            hybrid.warm_start = warm_start;
+        }
+        if let Some(clearance) = self.clearance {
+            let cost = ClearanceCost {
+                weight: clearance.weight,
+                margin: clearance.margin,
+            };
+            astar.clearance_cost = cost;
+            hybrid.clearance_cost = cost;
+        }
+        if let Some(objective) = self.objective {
+            hybrid.objective = ObjectiveWeights {
+                length: objective.length,
+                turns: objective.turns,
+                turning_angle: objective.turning_angle,
+                clearance: objective.clearance,
+                clearance_margin: objective.clearance_margin,
+                terrain: objective.terrain,
+            };
+        }
+        if let Some(policy) = self.pheromone_policy {
+            hybrid.set_pheromone_policy(match policy {
+                PheromonePolicyConfig::Keep => PheromonePolicy::Keep,
+                PheromonePolicyConfig::Reset => PheromonePolicy::Reset,
+                PheromonePolicyConfig::Decay(factor) => PheromonePolicy::Decay(factor),
+                PheromonePolicyConfig::GoalIndependent => PheromonePolicy::GoalIndependent,
+            });
+        }
+
+        // Open the init ranges up first so a new min is never clamped against the old max.
+        let values = self.hybrid_values();
+        for (param, _) in values.iter() {
+            if matches!(param, HybridParam::InitAlphaMax | HybridParam::InitBetaMax) {
+                hybrid.set_param(*param, param.range().1);
+            }
+        }
+        for (param, value) in values {
+            hybrid.set_param(param, value);
+        }
+
+        hybrid.set_seed(self.seed);
+        hybrid.reset();
+        hybrid.reset_particles();
+        Ok(())
+    }
+}
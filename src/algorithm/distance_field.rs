@@ -0,0 +1,314 @@
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::algorithm::grid::Grid;
+
+/// Exact Euclidean distance transform of the wall cells, with the nearest wall cell kept per cell.
+#[derive(Debug, Clone)]
+pub struct DistanceField {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    walls: Vec<bool>,
+    nearest: Vec<Option<usize>>,
+}
+
+impl DistanceField {
+    /// Felzenszwalb-Huttenlocher transform: one pass down the columns, one along the rows.
+    pub fn from_grid(grid: &Grid) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let walls: Vec<bool> = (0..width * height)
+            .map(|i| grid.is_wall(i % width, i / width) == Some(true))
+            .collect();
+
+        // Squared distance to, and row of, the nearest wall in the same column.
+        let mut column_dist = vec![f32::INFINITY; width * height];
+        let mut column_arg = vec![0; width * height];
+        let mut f = vec![0.0; height];
+        let mut d = vec![0.0; height];
+        let mut arg = vec![0; height];
+        for x in 0..width {
+            for y in 0..height {
+                f[y] = if walls[y * width + x] { 0.0 } else { f32::INFINITY };
+            }
+            squared_distance_1d(&f, &mut d, &mut arg);
+            for y in 0..height {
+                column_dist[y * width + x] = d[y];
+                column_arg[y * width + x] = arg[y];
+            }
+        }
+
+        let mut nearest = vec![None; width * height];
+        let mut f = vec![0.0; width];
+        let mut d = vec![0.0; width];
+        let mut arg = vec![0; width];
+        for y in 0..height {
+            f.copy_from_slice(&column_dist[y * width..(y + 1) * width]);
+            squared_distance_1d(&f, &mut d, &mut arg);
+            for x in 0..width {
+                if d[x].is_finite() {
+                    let wall_x = arg[x];
+                    let wall_y = column_arg[y * width + wall_x];
+                    nearest[y * width + x] = Some(wall_y * width + wall_x);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            cell_size: grid.cell_size(),
+            root: grid.root(),
+            walls,
+            nearest,
+        }
+    }
+
+    /// Closest point on the nearest wall, or `pos` itself when it lies inside a wall.
+    pub fn nearest_obstacle(&self, pos: Vec2) -> Option<Vec2> {
+        let (cx, cy) = self.clamped_cell(pos)?;
+        if self.contains(pos) && self.walls[cy * self.width + cx] {
+            return Some(pos);
+        }
+
+        // Cell centers are exact; off-center points may be closer to a neighbour's nearest wall.
+        let mut best: Option<(f32, Vec2)> = None;
+        for ny in cy.saturating_sub(1)..(cy + 2).min(self.height) {
+            for nx in cx.saturating_sub(1)..(cx + 2).min(self.width) {
+                let Some(wall) = self.nearest[ny * self.width + nx] else {
+                    continue;
+                };
+                let min = self.cell_corner(wall % self.width, wall / self.width);
+                let point = pos.clamp(min, min + Vec2::splat(self.cell_size));
+                let distance = pos.distance_squared(point);
+                if best.is_none_or(|(x, _)| distance < x) {
+                    best = Some((distance, point));
+                }
+            }
+        }
+        best.map(|(_, point)| point)
+    }
+
+    /// Distance from `pos` to the nearest wall edge; infinite when the grid has no walls.
+    pub fn distance_at(&self, pos: Vec2) -> f32 {
+        self.nearest_obstacle(pos)
+            .map_or(f32::INFINITY, |x| pos.distance(x))
+    }
+
+    /// Unit vector pointing away from the nearest wall; zero inside walls or without any.
+    pub fn gradient(&self, pos: Vec2) -> Vec2 {
+        self.nearest_obstacle(pos)
+            .map_or(Vec2::ZERO, |x| (pos - x).normalize_or_zero())
+    }
+
+    fn contains(&self, pos: Vec2) -> bool {
+        let local = (pos - self.root) / self.cell_size;
+        local.x >= 0.0
+            && local.y >= 0.0
+            && local.x < self.width as f32
+            && local.y < self.height as f32
+    }
+
+    fn clamped_cell(&self, pos: Vec2) -> Option<(usize, usize)> {
+        if self.width == 0 || self.height == 0 || pos.is_nan() {
+            return None;
+        }
+        let local = (pos - self.root) / self.cell_size;
+        Some((
+            (local.x.max(0.0) as usize).min(self.width - 1),
+            (local.y.max(0.0) as usize).min(self.height - 1),
+        ))
+    }
+
+    fn cell_corner(&self, x: usize, y: usize) -> Vec2 {
+        self.root + Vec2::new(x as f32, y as f32) * self.cell_size
+    }
+}
+
+/// Shortest 8-connected travel distance from every cell to one goal, walls excluded.
+#[derive(Debug, Clone)]
+pub struct GeodesicField {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    goal: Vec2,
+    goal_cell: (usize, usize),
+    walls: Vec<bool>,
+    cost: Vec<f32>,
+}
+
+impl GeodesicField {
+    /// Reverse Dijkstra out of the goal's cell. `None` when the goal is outside the grid or in a wall.
+    pub fn from_goal(grid: &Grid, goal: Vec2) -> Option<Self> {
+        let goal_cell = grid.get_from_world_pos(goal)?;
+        if grid.is_wall(goal_cell.0, goal_cell.1)? {
+            return None;
+        }
+
+        let (width, height) = (grid.width(), grid.height());
+        let walls: Vec<bool> = (0..width * height)
+            .map(|i| grid.is_wall(i % width, i / width) == Some(true))
+            .collect();
+
+        let mut field = Self {
+            width,
+            height,
+            cell_size: grid.cell_size(),
+            root: grid.root(),
+            goal,
+            goal_cell,
+            walls,
+            cost: vec![f32::INFINITY; width * height],
+        };
+
+        let start = goal_cell.1 * width + goal_cell.0;
+        field.cost[start] = goal.distance(field.cell_center(goal_cell.0, goal_cell.1));
+
+        let mut queue = BinaryHeap::new();
+        queue.push(FieldEntry(field.cost[start], start));
+        while let Some(FieldEntry(cost, index)) = queue.pop() {
+            if cost > field.cost[index] {
+                continue;
+            }
+            let (x, y) = (index % width, index / width);
+            let neighbours: Vec<_> = field.neighbours(x, y).collect();
+            for (nx, ny) in neighbours {
+                let step = if nx != x && ny != y {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                let next = ny * width + nx;
+                let candidate = cost + step * field.cell_size;
+                if candidate < field.cost[next] {
+                    field.cost[next] = candidate;
+                    queue.push(FieldEntry(candidate, next));
+                }
+            }
+        }
+
+        Some(field)
+    }
+
+    pub fn goal(&self) -> Vec2 {
+        self.goal
+    }
+
+    /// Travel distance from the center of cell `(x, y)`; infinite for walls and cut-off cells.
+    pub fn cost(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.cost[y * self.width + x])
+    }
+
+    /// Travel distance from `pos` to the goal, or `None` when the goal cannot be reached.
+    pub fn distance_at(&self, pos: Vec2) -> Option<f32> {
+        let local = (pos - self.root) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (local.x as usize, local.y as usize);
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        if (x, y) == self.goal_cell {
+            return Some(pos.distance(self.goal));
+        }
+
+        // Leave the cell through whichever reachable neighbour center is cheapest.
+        std::iter::once((x, y))
+            .chain(self.neighbours(x, y))
+            .filter(|&(nx, ny)| !self.walls[ny * self.width + nx])
+            .map(|(nx, ny)| self.cost[ny * self.width + nx] + pos.distance(self.cell_center(nx, ny)))
+            .filter(|x| x.is_finite())
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Air neighbours of a cell; diagonals need both adjacent sides open so walls are never cut.
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let is_air = move |nx: isize, ny: isize| {
+            nx >= 0
+                && ny >= 0
+                && (nx as usize) < self.width
+                && (ny as usize) < self.height
+                && !self.walls[ny as usize * self.width + nx as usize]
+        };
+        let (x, y) = (x as isize, y as isize);
+        [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)]
+            .into_iter()
+            .filter(move |&(dx, dy)| {
+                is_air(x + dx, y + dy) && (dx == 0 || dy == 0 || (is_air(x + dx, y) && is_air(x, y + dy)))
+            })
+            .map(move |(dx, dy)| ((x + dx) as usize, (y + dy) as usize))
+    }
+
+    fn cell_center(&self, x: usize, y: usize) -> Vec2 {
+        self.root + (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size
+    }
+}
+
+/// Min-heap entry ordered by cost.
+struct FieldEntry(f32, usize);
+
+impl PartialEq for FieldEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for FieldEntry {}
+
+impl Ord for FieldEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for FieldEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Squared 1D distance transform of `f` (lower envelope of parabolas), with the source index per sample.
+fn squared_distance_1d(f: &[f32], d: &mut [f32], arg: &mut [usize]) {
+    let n = f.len();
+    let mut v = Vec::with_capacity(n);
+    let mut z: Vec<f32> = Vec::with_capacity(n + 1);
+
+    for q in (0..n).filter(|&q| f[q].is_finite()) {
+        let parabola = |p: usize| f[p] + (p * p) as f32;
+        let mut s = f32::NEG_INFINITY;
+        while let Some(&p) = v.last() {
+            s = (parabola(q) - parabola(p)) / (2.0 * (q as f32 - p as f32));
+            if s <= z[z.len() - 1] {
+                v.pop();
+                z.pop();
+                s = f32::NEG_INFINITY;
+            } else {
+                break;
+            }
+        }
+        v.push(q);
+        z.push(s);
+    }
+
+    if v.is_empty() {
+        d.fill(f32::INFINITY);
+        return;
+    }
+
+    let mut k = 0;
+    for q in 0..n {
+        while k + 1 < v.len() && z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let p = v[k];
+        d[q] = (q as f32 - p as f32).powi(2) + f[p];
+        arg[q] = p;
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use std::sync::Arc;
+
+use crate::algorithm::distance_field::GeodesicField;
+use crate::algorithm::grid::Grid;
+
+/// Steering for any number of agents heading to one goal. The integration field is the grid's
+/// cached geodesic field, so its distances double as an exact heuristic for other solvers.
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    revision: u64,
+    integration: Arc<GeodesicField>,
+    directions: Vec<Option<Vec2>>,
+}
+
+impl FlowField {
+    /// `None` when the goal is outside the grid or in a wall.
+    pub fn from_grid(grid: &Grid, goal: Vec2) -> Option<Self> {
+        let integration = grid.geodesic_field(goal)?;
+        let goal_cell = grid.get_from_world_pos(goal)?;
+        let (width, height) = (grid.width(), grid.height());
+        let cell_size = grid.cell_size();
+        let center = |x: usize, y: usize| grid.root() + (Vec2::new(x as f32, y as f32) + 0.5) * cell_size;
+
+        // Each reachable cell points along its shortest-path step, which is the neighbour whose
+        // cost plus the step length is lowest.
+        let directions = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if !integration.cost(x, y)?.is_finite() {
+                    return None;
+                }
+                if (x, y) == goal_cell {
+                    return Some((goal - center(x, y)).normalize_or_zero());
+                }
+                integration
+                    .neighbours(x, y)
+                    .filter_map(|(nx, ny)| {
+                        let cost = integration.cost(nx, ny)? + center(x, y).distance(center(nx, ny));
+                        cost.is_finite().then_some((cost, (nx, ny)))
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .map(|(_, (nx, ny))| (center(nx, ny) - center(x, y)).normalize_or_zero())
+            })
+            .collect();
+
+        Some(Self {
+            width,
+            height,
+            cell_size,
+            root: grid.root(),
+            revision: grid.revision(),
+            integration,
+            directions,
+        })
+    }
+
+    pub fn goal(&self) -> Vec2 {
+        self.integration.goal()
+    }
+
+    /// Grid revision the field was built from.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Integration value of cell `(x, y)`; infinite for walls and cells cut off from the goal.
+    pub fn cost(&self, x: usize, y: usize) -> f32 {
+        self.integration.cost(x, y).unwrap_or(f32::INFINITY)
+    }
+
+    /// Unit step direction of cell `(x, y)`, `None` where the goal cannot be reached.
+    pub fn direction(&self, x: usize, y: usize) -> Option<Vec2> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.directions[y * self.width + x]
+    }
+
+    /// Direction of the cell under `pos`.
+    pub fn direction_at(&self, pos: Vec2) -> Option<Vec2> {
+        let local = (pos - self.root) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        self.direction(local.x as usize, local.y as usize)
+    }
+
+    /// Geodesic travel distance from `pos` to the goal.
+    pub fn distance_at(&self, pos: Vec2) -> Option<f32> {
+        self.integration.distance_at(pos)
+    }
+
+    /// The most expensive reachable cell, for normalising colours.
+    pub fn max_cost(&self) -> f32 {
+        (0..self.width * self.height)
+            .map(|i| self.cost(i % self.width, i / self.width))
+            .filter(|x| x.is_finite())
+            .fold(0.0, f32::max)
+    }
+}
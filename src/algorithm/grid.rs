@@ -1,5 +1,8 @@
 use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
 
+use crate::algorithm::distance_field::{DistanceField, GeodesicField};
 use crate::algorithm::types::{Quad, Ray, RayHitInfo};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -8,6 +11,16 @@ pub enum GridCell {
     Wall,
 }
 
+/// Cells changed by `Grid::set` are remembered this far back for incremental solvers.
+const CHANGE_LOG_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange {
+    pub x: usize,
+    pub y: usize,
+    pub revision: u64,
+}
+
 #[derive(Debug)]
 pub struct Grid {
     width: usize,
@@ -16,6 +29,11 @@ pub struct Grid {
     pixel_size: f32,
     root: Vec2,
     data: Vec<GridCell>,
+    terrain: Vec<f32>,
+    revision: u64,
+    changes: VecDeque<CellChange>,
+    distance_field: OnceLock<Arc<DistanceField>>,
+    geodesic_field: Mutex<Option<Arc<GeodesicField>>>,
 }
 
 impl Grid {
@@ -27,6 +45,11 @@ impl Grid {
             pixel_size,
             root,
             data: vec![GridCell::Air; width * height],
+            terrain: vec![1.0; width * height],
+            revision: 0,
+            changes: VecDeque::new(),
+            distance_field: OnceLock::new(),
+            geodesic_field: Mutex::new(None),
         }
     }
 
@@ -39,17 +62,115 @@ impl Grid {
     pub fn pixel_size(&self) -> f32 {
         self.pixel_size
     }
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+    pub fn root(&self) -> Vec2 {
+        self.root
+    }
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
 
     pub fn set(&mut self, x: usize, y: usize, val: GridCell) -> Option<()> {
         let pos = self.get_grid_pos(x, y)?;
-        self.data[pos] = val;
+        if self.data[pos] != val {
+            self.data[pos] = val;
+            self.record_change(x, y);
+            self.distance_field = OnceLock::new();
+            self.geodesic_field = Mutex::new(None);
+        }
+        Some(())
+    }
+
+    /// Cost multiplier for driving through cell `(x, y)`; plain ground is 1.
+    pub fn terrain(&self, x: usize, y: usize) -> Option<f32> {
+        let pos = self.get_grid_pos(x, y)?;
+        Some(self.terrain[pos])
+    }
+
+    pub fn set_terrain(&mut self, x: usize, y: usize, cost: f32) -> Option<()> {
+        let pos = self.get_grid_pos(x, y)?;
+        if self.terrain[pos] != cost {
+            self.terrain[pos] = cost;
+            self.record_change(x, y);
+        }
         Some(())
     }
+
+    pub fn terrain_at(&self, pos: Vec2) -> f32 {
+        self.get_from_world_pos(pos)
+            .and_then(|(x, y)| self.terrain(x, y))
+            .unwrap_or(1.0)
+    }
+
+    fn record_change(&mut self, x: usize, y: usize) {
+        self.revision += 1;
+        if self.changes.len() == CHANGE_LOG_SIZE {
+            self.changes.pop_front();
+        }
+        self.changes.push_back(CellChange {
+            x,
+            y,
+            revision: self.revision,
+        });
+    }
+    /// Cells changed after `revision`, oldest first. `None` when the log no longer reaches back that far.
+    pub fn changes_since(&self, revision: u64) -> Option<Vec<CellChange>> {
+        if revision > self.revision {
+            return None;
+        }
+        if revision == self.revision {
+            return Some(Vec::new());
+        }
+        let oldest = self.changes.front()?.revision;
+        if oldest > revision + 1 {
+            return None;
+        }
+        Some(
+            self.changes
+                .iter()
+                .filter(|x| x.revision > revision)
+                .copied()
+                .collect(),
+        )
+    }
+
     pub fn get(&self, x: usize, y: usize) -> Option<GridCell> {
         let pos = self.get_grid_pos(x, y)?;
         Some(self.data[pos].clone())
     }
 
+    /// Distance transform of the current walls, built on first use after each change.
+    pub fn distance_field(&self) -> Arc<DistanceField> {
+        self.distance_field
+            .get_or_init(|| Arc::new(DistanceField::from_grid(self)))
+            .clone()
+    }
+
+    pub fn distance_to_obstacle(&self, pos: Vec2) -> f32 {
+        self.distance_field().distance_at(pos)
+    }
+
+    pub fn nearest_obstacle(&self, pos: Vec2) -> Option<Vec2> {
+        self.distance_field().nearest_obstacle(pos)
+    }
+
+    pub fn distance_gradient(&self, pos: Vec2) -> Vec2 {
+        self.distance_field().gradient(pos)
+    }
+
+    /// Travel distance field towards `goal`; the last one is kept until the goal or the walls change.
+    pub fn geodesic_field(&self, goal: Vec2) -> Option<Arc<GeodesicField>> {
+        let mut cached = self.geodesic_field.lock().unwrap();
+        if let Some(field) = cached.as_ref().filter(|x| x.goal() == goal) {
+            return Some(field.clone());
+        }
+        let field = Arc::new(GeodesicField::from_goal(self, goal)?);
+        *cached = Some(field.clone());
+        Some(field)
+    }
+
     pub fn world_width(&self) -> f32 {
         self.width as f32 * self.cell_size
     }
@@ -234,6 +355,51 @@ impl Grid {
 
         None
     }
+
+    pub fn has_sight(&self, from: Vec2, to: Vec2) -> bool {
+        let distance = from.distance(to);
+        if distance == 0.0 {
+            return true;
+        }
+
+        self.raycast(Ray {
+            root: from,
+            dir: to - from,
+        })
+        .is_none_or(|hit| hit.dist >= distance)
+    }
+
+    /// Whether a circle of `radius` can sweep from `from` to `to` without touching a wall.
+    pub fn is_segment_clear(&self, from: Vec2, to: Vec2, radius: f32) -> bool {
+        if radius <= 0.0 {
+            return self.has_sight(from, to);
+        }
+
+        let min = from.min(to) - Vec2::splat(radius) - self.root;
+        let max = from.max(to) + Vec2::splat(radius) - self.root;
+        if max.x < 0.0 || max.y < 0.0 {
+            return true;
+        }
+
+        let x_min = (min.x / self.cell_size).floor().max(0.0) as usize;
+        let y_min = (min.y / self.cell_size).floor().max(0.0) as usize;
+        let x_max = ((max.x / self.cell_size).floor() as usize).min(self.width.saturating_sub(1));
+        let y_max = ((max.y / self.cell_size).floor() as usize).min(self.height.saturating_sub(1));
+
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                if self.is_wall(x, y) != Some(true) {
+                    continue;
+                }
+                let quad = self.get_cell_quad(x, y).unwrap();
+                if segment_quad_distance(from, to, quad) < radius {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     fn box_intersection(
         &self,
         ray_root: Vec2,
@@ -269,6 +435,11 @@ impl Grid {
             pixel_size: self.pixel_size,
             root: self.root,
             data: self.data.clone(),
+            terrain: self.terrain.clone(),
+            revision: self.revision,
+            changes: self.changes.clone(),
+            distance_field: self.distance_field.clone(),
+            geodesic_field: Mutex::new(self.geodesic_field.lock().unwrap().clone()),
         }
     }
 
@@ -280,3 +451,48 @@ impl Grid {
         Some(y * self.width + x)
     }
 }
+
+fn segment_quad_distance(from: Vec2, to: Vec2, quad: Quad) -> f32 {
+    let min = quad.pos;
+    let max = quad.pos + quad.siz;
+
+    // Slab test: a segment that enters the box has distance zero.
+    let dir = to - from;
+    let mut t_enter = 0.0_f32;
+    let mut t_exit = 1.0_f32;
+    let mut inside = true;
+    for axis in 0..2 {
+        if dir[axis].abs() < f32::EPSILON {
+            if from[axis] < min[axis] || from[axis] > max[axis] {
+                inside = false;
+                break;
+            }
+        } else {
+            let t1 = (min[axis] - from[axis]) / dir[axis];
+            let t2 = (max[axis] - from[axis]) / dir[axis];
+            t_enter = t_enter.max(t1.min(t2));
+            t_exit = t_exit.min(t1.max(t2));
+        }
+    }
+    if inside && t_enter <= t_exit {
+        return 0.0;
+    }
+
+    let point_quad = |p: Vec2| p.distance(p.clamp(min, max));
+    let point_segment = |p: Vec2| {
+        let len_sq = dir.length_squared();
+        let t = if len_sq > 0.0 { ((p - from).dot(dir) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+        p.distance(from + dir * t)
+    };
+
+    [
+        point_quad(from),
+        point_quad(to),
+        point_segment(min),
+        point_segment(max),
+        point_segment(Vec2::new(min.x, max.y)),
+        point_segment(Vec2::new(max.x, min.y)),
+    ]
+    .into_iter()
+    .fold(f32::INFINITY, f32::min)
+}
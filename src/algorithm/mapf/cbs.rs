@@ -0,0 +1,138 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+use super::low_level::{self, Constraints};
+use super::{Cell, CellGraph, Conflict, MapfSolution, find_conflicts};
+use crate::algorithm::grid::Grid;
+use crate::algorithm::problem::AgentGoal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Constraint {
+    Vertex { agent: usize, cell: Cell, time: usize },
+    Edge { agent: usize, from: Cell, to: Cell, time: usize },
+}
+
+impl Constraint {
+    fn agent(&self) -> usize {
+        match *self {
+            Constraint::Vertex { agent, .. } | Constraint::Edge { agent, .. } => agent,
+        }
+    }
+}
+
+struct ConstraintNode {
+    constraints: Vec<Constraint>,
+    paths: Vec<Vec<Cell>>,
+    cost: usize,
+}
+
+/// Conflict-Based Search (Sharon et al.): a best-first tree over constraint sets,
+/// replanning one agent with time-expanded A* per branch.
+#[derive(Clone)]
+pub struct CbsSolver {
+    pub max_nodes: usize,
+    grid: Arc<Mutex<Grid>>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl CbsSolver {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            max_nodes: 5000,
+            grid,
+            cancel_flag: None,
+        }
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    /// Conflict-free plans minimising the sum of costs, or `None` when the node budget runs out.
+    pub fn solve(&self, agents: &[AgentGoal]) -> Option<MapfSolution> {
+        let graph = CellGraph::from_grid(&self.grid.lock().unwrap());
+        let endpoints = graph.endpoints(agents)?;
+        let heuristics: Vec<Vec<u32>> = endpoints.iter().map(|x| graph.distances_to(x.1)).collect();
+
+        let plan_agent = |agent: usize, constraints: &[Constraint]| {
+            let mut agent_constraints = Constraints::default();
+            for constraint in constraints.iter().filter(|x| x.agent() == agent) {
+                match *constraint {
+                    Constraint::Vertex { cell, time, .. } => {
+                        agent_constraints.vertex.insert((cell, time));
+                    }
+                    Constraint::Edge { from, to, time, .. } => {
+                        agent_constraints.edge.insert((from, to, time));
+                    }
+                }
+            }
+            let (start, goal) = endpoints[agent];
+            low_level::plan(&graph, start, goal, &heuristics[agent], &agent_constraints).0
+        };
+
+        let paths: Vec<Vec<Cell>> = (0..agents.len())
+            .map(|agent| plan_agent(agent, &[]))
+            .collect::<Option<_>>()?;
+        let mut nodes = vec![ConstraintNode {
+            cost: paths.iter().map(|x| x.len() - 1).sum(),
+            constraints: Vec::new(),
+            paths,
+        }];
+
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((nodes[0].cost, 0)));
+        let mut expanded = 0;
+
+        while let Some(Reverse((_, index))) = open.pop() {
+            if expanded >= self.max_nodes || self.is_cancelled() {
+                return None;
+            }
+            expanded += 1;
+
+            let Some(conflict) = find_conflicts(&nodes[index].paths, true).first().copied() else {
+                return Some(graph.solution(&nodes[index].paths, expanded));
+            };
+
+            let branches = match conflict {
+                Conflict::Vertex { a, b, cell, time } => [
+                    Constraint::Vertex { agent: a, cell, time },
+                    Constraint::Vertex { agent: b, cell, time },
+                ],
+                Conflict::Edge { a, b, from, to, time } => [
+                    Constraint::Edge { agent: a, from, to, time },
+                    Constraint::Edge { agent: b, from: to, to: from, time },
+                ],
+            };
+
+            for constraint in branches {
+                let mut constraints = nodes[index].constraints.clone();
+                constraints.push(constraint);
+                let agent = constraint.agent();
+                let Some(path) = plan_agent(agent, &constraints) else {
+                    continue;
+                };
+
+                let mut paths = nodes[index].paths.clone();
+                paths[agent] = path;
+                let cost = paths.iter().map(|x| x.len() - 1).sum();
+                nodes.push(ConstraintNode {
+                    constraints,
+                    paths,
+                    cost,
+                });
+                open.push(Reverse((cost, nodes.len() - 1)));
+            }
+        }
+        None
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
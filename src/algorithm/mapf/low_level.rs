@@ -0,0 +1,90 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::{Cell, CellGraph};
+
+/// What one agent may not do, collected from the CBS branch it is planned in.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Constraints {
+    pub vertex: HashSet<(Cell, usize)>,
+    pub edge: HashSet<(Cell, Cell, usize)>,
+}
+
+impl Constraints {
+    /// The agent may only stop at `goal` after the last time it is forbidden there.
+    fn earliest_finish(&self, goal: Cell) -> usize {
+        self.vertex
+            .iter()
+            .filter(|x| x.0 == goal)
+            .map(|x| x.1 + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A* over (cell, time) with unit moves and waits. Returns the timed path and expanded states.
+pub(super) fn plan(
+    graph: &CellGraph,
+    start: Cell,
+    goal: Cell,
+    heuristic: &[u32],
+    constraints: &Constraints,
+) -> (Option<Vec<Cell>>, usize) {
+    if heuristic[graph.index(start)] == u32::MAX {
+        return (None, 0);
+    }
+
+    let earliest_finish = constraints.earliest_finish(goal);
+    let latest_constraint = constraints
+        .vertex
+        .iter()
+        .map(|x| x.1)
+        .chain(constraints.edge.iter().map(|x| x.2))
+        .max()
+        .unwrap_or(0);
+    // Waiting past every constraint never helps, so the search space is finite.
+    let horizon = latest_constraint + graph.cell_count() + 1;
+
+    let h = |cell: Cell| heuristic[graph.index(cell)] as usize;
+    let mut open = BinaryHeap::new();
+    let mut parents: HashMap<(Cell, usize), Cell> = HashMap::new();
+    let mut closed: HashSet<(Cell, usize)> = HashSet::new();
+    let mut expanded = 0;
+
+    open.push((Reverse(h(start)), 0, start));
+    while let Some((_, time, cell)) = open.pop() {
+        if !closed.insert((cell, time)) {
+            continue;
+        }
+        expanded += 1;
+
+        if cell == goal && time >= earliest_finish {
+            let mut path = vec![cell];
+            let mut key = (cell, time);
+            while let Some(&parent) = parents.get(&key) {
+                path.push(parent);
+                key = (parent, key.1 - 1);
+            }
+            path.reverse();
+            return (Some(path), expanded);
+        }
+        if time >= horizon {
+            continue;
+        }
+
+        let next_time = time + 1;
+        for next in std::iter::once(cell).chain(graph.neighbours(cell)) {
+            if constraints.vertex.contains(&(next, next_time))
+                || constraints.edge.contains(&(cell, next, time))
+                || closed.contains(&(next, next_time))
+            {
+                continue;
+            }
+            if let std::collections::hash_map::Entry::Vacant(entry) = parents.entry((next, next_time)) {
+                entry.insert(cell);
+                open.push((Reverse(next_time + h(next)), next_time, next));
+            }
+        }
+    }
+    (None, expanded)
+}
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::algorithm::grid::Grid;
+use crate::algorithm::problem::AgentGoal;
+
+pub mod cbs;
+mod low_level;
+pub mod multi_colony;
+
+pub type Cell = (usize, usize);
+
+/// Timed plans for every agent: entry `t` of a path is where that agent stands at step `t`.
+#[derive(Debug, Clone)]
+pub struct MapfSolution {
+    pub paths: Vec<Vec<Vec2>>,
+    pub sum_of_costs: usize,
+    pub makespan: usize,
+    pub conflicts: usize,
+    pub expanded: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Agents `a` and `b` stand on `cell` at `time`.
+    Vertex { a: usize, b: usize, cell: Cell, time: usize },
+    /// Agent `a` moves `from` -> `to` between `time` and `time + 1` while `b` moves the other way.
+    Edge { a: usize, b: usize, from: Cell, to: Cell, time: usize },
+}
+
+/// Agents stay on their goal once their path ends.
+fn position(path: &[Cell], time: usize) -> Cell {
+    path[time.min(path.len() - 1)]
+}
+
+/// Conflicts in time order; stops after the first one when `first_only` is set.
+pub fn find_conflicts(paths: &[Vec<Cell>], first_only: bool) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let horizon = paths.iter().map(|x| x.len()).max().unwrap_or(0);
+
+    for time in 0..horizon {
+        for a in 0..paths.len() {
+            for b in a + 1..paths.len() {
+                let (pa, pb) = (position(&paths[a], time), position(&paths[b], time));
+                if pa == pb {
+                    conflicts.push(Conflict::Vertex { a, b, cell: pa, time });
+                } else if time + 1 < horizon
+                    && position(&paths[a], time + 1) == pb
+                    && position(&paths[b], time + 1) == pa
+                {
+                    conflicts.push(Conflict::Edge { a, b, from: pa, to: pb, time });
+                }
+                if first_only && !conflicts.is_empty() {
+                    return conflicts;
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Snapshot of the walls as a 4-connected cell graph, shared by the MAPF solvers.
+struct CellGraph {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    walls: Vec<bool>,
+}
+
+impl CellGraph {
+    fn from_grid(grid: &Grid) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        Self {
+            width,
+            height,
+            cell_size: grid.cell_size(),
+            root: grid.root(),
+            walls: (0..width * height)
+                .map(|i| grid.is_wall(i % width, i / width) == Some(true))
+                .collect(),
+        }
+    }
+
+    fn index(&self, cell: Cell) -> usize {
+        cell.1 * self.width + cell.0
+    }
+
+    fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn cell_of(&self, pos: Vec2) -> Option<Cell> {
+        let local = (pos - self.root) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let cell = (local.x as usize, local.y as usize);
+        (cell.0 < self.width && cell.1 < self.height && !self.walls[self.index(cell)]).then_some(cell)
+    }
+
+    fn center(&self, cell: Cell) -> Vec2 {
+        self.root + (Vec2::new(cell.0 as f32, cell.1 as f32) + 0.5) * self.cell_size
+    }
+
+    fn neighbours(&self, cell: Cell) -> impl Iterator<Item = Cell> + '_ {
+        let (x, y) = (cell.0 as isize, cell.1 as isize);
+        [(1, 0), (0, -1), (-1, 0), (0, 1)]
+            .into_iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height)
+            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .filter(|&x| !self.walls[self.index(x)])
+    }
+
+    /// Steps from every cell to `goal`; `u32::MAX` where it cannot be reached.
+    fn distances_to(&self, goal: Cell) -> Vec<u32> {
+        let mut distances = vec![u32::MAX; self.cell_count()];
+        let mut queue = VecDeque::new();
+        distances[self.index(goal)] = 0;
+        queue.push_back(goal);
+        while let Some(cell) = queue.pop_front() {
+            let next = distances[self.index(cell)] + 1;
+            for neighbour in self.neighbours(cell) {
+                let index = self.index(neighbour);
+                if distances[index] == u32::MAX {
+                    distances[index] = next;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Start and goal cells per agent; `None` if any lies in a wall or two agents share one.
+    fn endpoints(&self, agents: &[AgentGoal]) -> Option<Vec<(Cell, Cell)>> {
+        let endpoints: Vec<(Cell, Cell)> = agents
+            .iter()
+            .map(|x| Some((self.cell_of(x.start)?, self.cell_of(x.goal)?)))
+            .collect::<Option<_>>()?;
+        for (i, a) in endpoints.iter().enumerate() {
+            if endpoints[i + 1..].iter().any(|b| a.0 == b.0 || a.1 == b.1) {
+                return None;
+            }
+        }
+        Some(endpoints)
+    }
+
+    fn solution(&self, paths: &[Vec<Cell>], expanded: usize) -> MapfSolution {
+        MapfSolution {
+            paths: paths
+                .iter()
+                .map(|x| x.iter().map(|&cell| self.center(cell)).collect())
+                .collect(),
+            sum_of_costs: paths.iter().map(|x| x.len() - 1).sum(),
+            makespan: paths.iter().map(|x| x.len() - 1).max().unwrap_or(0),
+            conflicts: find_conflicts(paths, false).len(),
+            expanded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::grid::GridCell;
+    use std::sync::{Arc, Mutex};
+
+    /// A `width` x `height` grid of unit cells with the given walls.
+    fn grid(width: usize, height: usize, walls: &[Cell]) -> Grid {
+        let mut grid = Grid::new(width, height, 1.0, 1.0, Vec2::ZERO);
+        for &(x, y) in walls {
+            grid.set(x, y, GridCell::Wall);
+        }
+        grid
+    }
+
+    fn center(cell: Cell) -> Vec2 {
+        Vec2::new(cell.0 as f32, cell.1 as f32) + 0.5
+    }
+
+    #[test]
+    fn swapping_cells_is_an_edge_conflict() {
+        let paths = vec![vec![(0, 0), (1, 0)], vec![(1, 0), (0, 0)]];
+        assert_eq!(
+            find_conflicts(&paths, false),
+            vec![Conflict::Edge { a: 0, b: 1, from: (0, 0), to: (1, 0), time: 0 }]
+        );
+    }
+
+    #[test]
+    fn cbs_resolves_a_swap_through_a_side_pocket() {
+        // A three-cell corridor with a pocket under its middle cell.
+        let grid = Arc::new(Mutex::new(grid(3, 2, &[(0, 1), (2, 1)])));
+        let agents = [
+            AgentGoal { start: center((0, 0)), goal: center((2, 0)) },
+            AgentGoal { start: center((2, 0)), goal: center((0, 0)) },
+        ];
+        let solution = cbs::CbsSolver::new(grid).solve(&agents).unwrap();
+        assert_eq!(solution.conflicts, 0);
+        for (path, agent) in solution.paths.iter().zip(agents.iter()) {
+            assert_eq!(path.first(), Some(&agent.start));
+            assert_eq!(path.last(), Some(&agent.goal));
+        }
+    }
+
+    #[test]
+    fn agent_waits_until_its_goal_is_free() {
+        let graph = CellGraph::from_grid(&grid(3, 1, &[]));
+        let (start, goal) = ((0, 0), (1, 0));
+        let mut constraints = low_level::Constraints::default();
+        constraints.vertex.insert((goal, 3));
+
+        let path = low_level::plan(&graph, start, goal, &graph.distances_to(goal), &constraints)
+            .0
+            .unwrap();
+        // Arriving at step 1 and staying would stand on the goal at step 3.
+        assert_eq!(path.len() - 1, 4);
+        assert_eq!(path.last(), Some(&goal));
+        assert_ne!(position(&path, 3), goal);
+    }
+
+    #[test]
+    fn shared_endpoints_are_rejected() {
+        let graph = CellGraph::from_grid(&grid(3, 1, &[]));
+        let agents = [
+            AgentGoal { start: center((0, 0)), goal: center((2, 0)) },
+            AgentGoal { start: center((1, 0)), goal: center((2, 0)) },
+        ];
+        assert!(graph.endpoints(&agents).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::collections::HashSet;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+use super::{Cell, CellGraph, MapfSolution, find_conflicts};
+use crate::algorithm::grid::Grid;
+use crate::algorithm::problem::AgentGoal;
+
+/// One ant colony per agent on the cell graph. Colonies are repelled by each other's
+/// pheromone so their routes spread apart; the result may still contain conflicts.
+#[derive(Clone)]
+pub struct MultiColonyAco {
+    pub ants_per_colony: usize,
+    pub iterations: usize,
+    pub alpha: f64,
+    pub beta: f64,
+    pub evaporation: f64,
+    pub deposit: f64,
+    pub repulsion: f64,
+
+    grid: Arc<Mutex<Grid>>,
+    seed: Option<u64>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl MultiColonyAco {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            ants_per_colony: 10,
+            iterations: 60,
+            alpha: 1.0,
+            beta: 3.0,
+            evaporation: 0.1,
+            deposit: 10.0,
+            repulsion: 0.5,
+            grid,
+            seed: None,
+            cancel_flag: None,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    /// Best joint plan found, ranked by conflicts first and sum of costs second.
+    pub fn solve(&self, agents: &[AgentGoal]) -> Option<MapfSolution> {
+        let graph = CellGraph::from_grid(&self.grid.lock().unwrap());
+        let endpoints = graph.endpoints(agents)?;
+        let heuristics: Vec<Vec<u32>> = endpoints.iter().map(|x| graph.distances_to(x.1)).collect();
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        let mut pheromones = vec![vec![1.0; graph.cell_count()]; agents.len()];
+        let mut best: Option<(usize, usize, Vec<Vec<Cell>>)> = None;
+        let mut walks = 0;
+
+        for _ in 0..self.iterations {
+            if self.is_cancelled() {
+                return None;
+            }
+
+            let mut iteration_paths = Vec::with_capacity(agents.len());
+            for colony in 0..agents.len() {
+                let mut colony_best: Option<Vec<Cell>> = None;
+                for _ in 0..self.ants_per_colony {
+                    walks += 1;
+                    let Some(path) = self.walk(&graph, colony, &endpoints, &heuristics, &pheromones, &mut rng) else {
+                        continue;
+                    };
+                    if colony_best.as_ref().is_none_or(|x| path.len() < x.len()) {
+                        colony_best = Some(path);
+                    }
+                }
+                iteration_paths.push(colony_best);
+            }
+            let Some(iteration_paths) = iteration_paths.into_iter().collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+
+            for colony in pheromones.iter_mut() {
+                for value in colony.iter_mut() {
+                    *value *= 1.0 - self.evaporation;
+                }
+            }
+            for (colony, path) in iteration_paths.iter().enumerate() {
+                let amount = self.deposit / path.len() as f64;
+                for &cell in path {
+                    pheromones[colony][graph.index(cell)] += amount;
+                }
+            }
+
+            let conflicts = find_conflicts(&iteration_paths, false).len();
+            let cost = iteration_paths.iter().map(|x| x.len() - 1).sum();
+            if best.as_ref().is_none_or(|x| (conflicts, cost) < (x.0, x.1)) {
+                best = Some((conflicts, cost, iteration_paths));
+            }
+        }
+
+        best.map(|(_, _, paths)| graph.solution(&paths, walks))
+    }
+
+    fn walk(
+        &self,
+        graph: &CellGraph,
+        colony: usize,
+        endpoints: &[(Cell, Cell)],
+        heuristics: &[Vec<u32>],
+        pheromones: &[Vec<f64>],
+        rng: &mut StdRng,
+    ) -> Option<Vec<Cell>> {
+        let (start, goal) = endpoints[colony];
+        let mut path = vec![start];
+        let mut visited = HashSet::from([start]);
+
+        while *path.last()? != goal {
+            let current = *path.last()?;
+            let options: Vec<(Cell, f64)> = graph
+                .neighbours(current)
+                .filter(|x| !visited.contains(x))
+                .map(|x| {
+                    let index = graph.index(x);
+                    let own = pheromones[colony][index];
+                    let others: f64 = (0..pheromones.len())
+                        .filter(|&c| c != colony)
+                        .map(|c| pheromones[c][index])
+                        .sum();
+                    let distance = heuristics[colony][index] as f64;
+                    let weight = own.powf(self.alpha) * (1.0 / (1.0 + distance)).powf(self.beta)
+                        / (1.0 + self.repulsion * others);
+                    (x, weight)
+                })
+                .collect();
+            let total: f64 = options.iter().map(|x| x.1).sum();
+            if options.is_empty() || total <= 0.0 {
+                return None;
+            }
+
+            let mut roll = rng.random_range(0.0..total);
+            let mut choice = options[options.len() - 1].0;
+            for &(cell, weight) in options.iter() {
+                roll -= weight;
+                if roll < 0.0 {
+                    choice = cell;
+                    break;
+                }
+            }
+            visited.insert(choice);
+            path.push(choice);
+        }
+        Some(path)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
@@ -1,4 +1,13 @@
 pub mod types;
 pub mod grid;
+pub mod clearance;
+pub mod distance_field;
+pub mod flow_field;
+pub mod objective;
 pub mod problem;
+pub mod path;
+pub mod post_process;
+pub mod config;
+pub mod mapf;
 pub mod solve;
+pub mod waypoints;
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::algorithm::grid::Grid;
+
+/// Turns sharper than this count towards `PathMetrics::turns`.
+const TURN_THRESHOLD: f32 = 1e-3;
+
+/// Everything a path is judged on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathMetrics {
+    pub length: f32,
+    pub turns: usize,
+    /// Sum of heading changes, in radians.
+    pub turning_angle: f32,
+    /// Smallest distance to a wall anywhere along the path.
+    pub min_clearance: f32,
+    /// Extra cost of rough cells, integrated over the distance driven through them.
+    pub terrain: f32,
+}
+
+impl PathMetrics {
+    /// Walks `path` in steps of the grid's pixel size, sampling clearance and terrain.
+    pub fn measure(grid: &Grid, path: &[Vec2]) -> Self {
+        let mut metrics = Self {
+            length: 0.0,
+            turns: 0,
+            turning_angle: 0.0,
+            min_clearance: path
+                .first()
+                .map_or(f32::INFINITY, |x| grid.distance_to_obstacle(*x)),
+            terrain: 0.0,
+        };
+
+        let step = grid.pixel_size();
+        for segment in path.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let length = from.distance(to);
+            metrics.length += length;
+
+            let samples = (length / step).ceil().max(1.0) as usize;
+            let sample_length = length / samples as f32;
+            for i in 0..samples {
+                let mid = from.lerp(to, (i as f32 + 0.5) / samples as f32);
+                let end = from.lerp(to, (i + 1) as f32 / samples as f32);
+                metrics.terrain += (grid.terrain_at(mid) - 1.0) * sample_length;
+                metrics.min_clearance = metrics.min_clearance.min(grid.distance_to_obstacle(end));
+            }
+        }
+
+        for corner in path.windows(3) {
+            let (a, b) = (corner[1] - corner[0], corner[2] - corner[1]);
+            if a == Vec2::ZERO || b == Vec2::ZERO {
+                continue;
+            }
+            let angle = a.angle_to(b).abs();
+            if angle > TURN_THRESHOLD {
+                metrics.turns += 1;
+                metrics.turning_angle += angle;
+            }
+        }
+        metrics
+    }
+
+    /// True when `self` is no worse than `other` everywhere and better somewhere.
+    pub fn dominates(&self, other: &Self) -> bool {
+        let pairs = [
+            (self.length, other.length),
+            (self.turns as f32, other.turns as f32),
+            (self.turning_angle, other.turning_angle),
+            (-self.min_clearance, -other.min_clearance),
+            (self.terrain, other.terrain),
+        ];
+        pairs.iter().all(|(a, b)| a <= b) && pairs.iter().any(|(a, b)| a < b)
+    }
+}
+
+/// Weights of the combined path cost. The default only counts length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectiveWeights {
+    pub length: f32,
+    pub turns: f32,
+    pub turning_angle: f32,
+    /// Charged per unit the path comes closer to a wall than `clearance_margin`.
+    pub clearance: f32,
+    pub clearance_margin: f32,
+    pub terrain: f32,
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        Self {
+            length: 1.0,
+            turns: 0.0,
+            turning_angle: 0.0,
+            clearance: 0.0,
+            clearance_margin: 40.0,
+            terrain: 0.0,
+        }
+    }
+}
+
+impl ObjectiveWeights {
+    /// Only length is weighted, and at 1, so the plain driven distance is the cost.
+    pub fn is_length_only(&self) -> bool {
+        self.length == 1.0
+            && self.turns == 0.0 && self.turning_angle == 0.0 && self.clearance == 0.0 && self.terrain == 0.0
+    }
+
+    pub fn cost(&self, metrics: &PathMetrics) -> f64 {
+        let clearance_deficit = (self.clearance_margin - metrics.min_clearance).max(0.0);
+        (self.length * metrics.length
+            + self.turns * metrics.turns as f32
+            + self.turning_angle * metrics.turning_angle
+            + self.clearance * clearance_deficit
+            + self.terrain * metrics.terrain) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParetoTour {
+    pub path: Vec<Vec2>,
+    pub metrics: PathMetrics,
+    pub cost: f64,
+}
+
+/// Non-dominated tours seen so far, sorted by length.
+#[derive(Debug, Clone)]
+pub struct ParetoFront {
+    pub capacity: usize,
+    tours: Vec<ParetoTour>,
+}
+
+impl ParetoFront {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tours: Vec::new(),
+        }
+    }
+
+    pub fn tours(&self) -> &[ParetoTour] {
+        &self.tours
+    }
+
+    pub fn clear(&mut self) {
+        self.tours.clear();
+    }
+
+    /// Adds `tour` unless something on the front dominates or equals it, and drops what it dominates.
+    /// Past capacity the tour with the highest weighted cost goes.
+    pub fn insert(&mut self, tour: ParetoTour) -> bool {
+        if self
+            .tours
+            .iter()
+            .any(|x| x.metrics.dominates(&tour.metrics) || x.metrics == tour.metrics)
+        {
+            return false;
+        }
+        self.tours.retain(|x| !tour.metrics.dominates(&x.metrics));
+
+        let index = self.tours.partition_point(|x| x.metrics.length <= tour.metrics.length);
+        self.tours.insert(index, tour);
+        if self.tours.len() > self.capacity {
+            let worst = (0..self.tours.len())
+                .max_by(|&a, &b| self.tours[a].cost.total_cmp(&self.tours[b].cost))
+                .unwrap();
+            self.tours.remove(worst);
+        }
+        true
+    }
+
+    /// Keeps the tours `keep` accepts, e.g. the ones walls have not cut.
+    pub fn retain(&mut self, keep: impl FnMut(&ParetoTour) -> bool) {
+        self.tours.retain(keep);
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::prelude::*;
+
+pub fn path_length(path: &[Vec2]) -> f32 {
+    path.windows(2).map(|x| x[0].distance(x[1])).sum()
+}
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use crate::algorithm::{grid::Grid, path::path_length};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smoothing {
+    None,
+    Chaikin,
+    CatmullRom,
+    BSpline,
+}
+
+impl Smoothing {
+    pub fn next(&self) -> Self {
+        match self {
+            Smoothing::None => Smoothing::Chaikin,
+            Smoothing::Chaikin => Smoothing::CatmullRom,
+            Smoothing::CatmullRom => Smoothing::BSpline,
+            Smoothing::BSpline => Smoothing::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostProcessReport {
+    pub raw_len: f32,
+    pub pruned_len: f32,
+    pub final_len: f32,
+    pub smoothing_rejected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcessor {
+    pub prune: bool,
+    pub smoothing: Smoothing,
+    /// Chaikin passes, or samples per segment for the spline variants.
+    pub resolution: usize,
+}
+
+impl Default for PostProcessor {
+    fn default() -> Self {
+        Self {
+            prune: false,
+            smoothing: Smoothing::None,
+            resolution: 4,
+        }
+    }
+}
+
+impl PostProcessor {
+    pub fn is_identity(&self) -> bool {
+        !self.prune && self.smoothing == Smoothing::None
+    }
+
+    /// Shortcuts and curves keep `agent_radius` clear of walls, like the path they replace.
+    pub fn process(
+        &self,
+        grid: &Grid,
+        path: &[Vec2],
+        agent_radius: f32,
+    ) -> (Vec<Vec2>, PostProcessReport) {
+        let raw_len = path_length(path);
+
+        let pruned = if self.prune {
+            string_pull(grid, path, agent_radius)
+        } else {
+            path.to_vec()
+        };
+        let pruned_len = path_length(&pruned);
+
+        let smoothed = match self.smoothing {
+            Smoothing::None => None,
+            Smoothing::Chaikin => Some(chaikin(&pruned, self.resolution)),
+            Smoothing::CatmullRom => Some(catmull_rom(&pruned, self.resolution)),
+            Smoothing::BSpline => Some(b_spline(&pruned, self.resolution)),
+        };
+
+        // Curves cut corners, so a smoothed path that clips a wall falls back to the polyline.
+        let smoothing_rejected = smoothed
+            .as_ref()
+            .is_some_and(|x| !is_collision_free(grid, x, agent_radius));
+        let result = match smoothed {
+            Some(smoothed) if !smoothing_rejected => smoothed,
+            _ => pruned,
+        };
+
+        let report = PostProcessReport {
+            raw_len,
+            pruned_len,
+            final_len: path_length(&result),
+            smoothing_rejected,
+        };
+        (result, report)
+    }
+}
+
+pub fn is_collision_free(grid: &Grid, path: &[Vec2], radius: f32) -> bool {
+    path.windows(2).all(|x| grid.is_segment_clear(x[0], x[1], radius))
+}
+
+/// Drops every waypoint that the previous kept waypoint can see past.
+pub fn string_pull(grid: &Grid, path: &[Vec2], radius: f32) -> Vec<Vec2> {
+    if path.len() <= 2 {
+        return path.to_vec();
+    }
+
+    let mut result = vec![path[0]];
+    let mut cur = 0;
+    while cur < path.len() - 1 {
+        let next = (cur + 1..path.len())
+            .rev()
+            .find(|&x| grid.is_segment_clear(path[cur], path[x], radius))
+            .unwrap_or(cur + 1);
+        result.push(path[next]);
+        cur = next;
+    }
+    result
+}
+
+pub fn chaikin(path: &[Vec2], iterations: usize) -> Vec<Vec2> {
+    let mut result = path.to_vec();
+    for _ in 0..iterations {
+        if result.len() <= 2 {
+            break;
+        }
+
+        let mut next = vec![result[0]];
+        for x in result.windows(2) {
+            next.push(x[0].lerp(x[1], 0.25));
+            next.push(x[0].lerp(x[1], 0.75));
+        }
+        next.push(*result.last().unwrap());
+        result = next;
+    }
+    result
+}
+
+pub fn catmull_rom(path: &[Vec2], samples: usize) -> Vec<Vec2> {
+    if path.len() <= 2 || samples == 0 {
+        return path.to_vec();
+    }
+
+    let point = |i: isize| path[i.clamp(0, path.len() as isize - 1) as usize];
+
+    let mut result = Vec::new();
+    for i in 0..path.len() as isize - 1 {
+        let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+        for s in 0..samples {
+            let t = s as f32 / samples as f32;
+            let t2 = t * t;
+            let t3 = t2 * t;
+            result.push(
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+            );
+        }
+    }
+    result.push(*path.last().unwrap());
+    result
+}
+
+pub fn b_spline(path: &[Vec2], samples: usize) -> Vec<Vec2> {
+    if path.len() <= 2 || samples == 0 {
+        return path.to_vec();
+    }
+
+    // Repeating the end points pins the curve to the start and goal.
+    let first = path[0];
+    let last = *path.last().unwrap();
+    let control: Vec<Vec2> = [first, first]
+        .into_iter()
+        .chain(path.iter().copied())
+        .chain([last, last])
+        .collect();
+
+    let mut result = Vec::new();
+    for x in control.windows(4) {
+        let (p0, p1, p2, p3) = (x[0], x[1], x[2], x[3]);
+        for s in 0..samples {
+            let t = s as f32 / samples as f32;
+            let t2 = t * t;
+            let t3 = t2 * t;
+            let b0 = (1.0 - t).powi(3) / 6.0;
+            let b1 = (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0;
+            let b2 = (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0;
+            let b3 = t3 / 6.0;
+            result.push(p0 * b0 + p1 * b1 + p2 * b2 + p3 * b3);
+        }
+    }
+    result.push(last);
+    result
+}
@@ -2,10 +2,22 @@ use std::sync::{Arc, Mutex};
 
 use bevy::math::Vec2;
 use crate::algorithm::grid::Grid;
+use crate::algorithm::waypoints::WaypointOrder;
+
+/// One start/goal pair of a multi-agent problem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgentGoal {
+    pub start: Vec2,
+    pub goal: Vec2,
+}
 
 #[derive(Debug, Clone)]
 pub struct Problem {
     pub grid: Arc<Mutex<Grid>>,
     pub start: Option<Vec2>,
     pub goal: Option<Vec2>,
+    pub agent_radius: f32,
+    pub agents: Vec<AgentGoal>,
+    pub waypoints: Vec<Vec2>,
+    pub waypoint_order: WaypointOrder,
 }
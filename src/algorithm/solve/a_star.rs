@@ -1,10 +1,16 @@
-use crate::algorithm::{grid::Grid, types::Ray};
+use crate::algorithm::{
+    clearance::{ClearanceCost, ClearanceMap},
+    grid::Grid,
+};
 use bevy::prelude::*;
 use std::{
     cmp::Ordering,
     collections::{BinaryHeap, HashMap},
     hash::{Hash, Hasher},
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -74,20 +80,86 @@ impl ProbeDirection {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchNode {
+    pub pos: Vec2,
+    pub g: f32,
+    pub f: f32,
+    pub opened_at: usize,
+    pub closed_at: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchTrace {
+    pub nodes: Vec<SearchNode>,
+    pub expansions: usize,
+}
+
+impl SearchTrace {
+    fn open(&mut self, index: &mut HashMap<(u32, u32), usize>, key: (u32, u32), node: &Node) {
+        if let Some(&i) = index.get(&key) {
+            self.nodes[i].g = node.g;
+            self.nodes[i].f = node.f();
+        } else {
+            index.insert(key, self.nodes.len());
+            self.nodes.push(SearchNode {
+                pos: node.pos,
+                g: node.g,
+                f: node.f(),
+                opened_at: self.expansions,
+                closed_at: None,
+            });
+        }
+    }
+
+    fn close(&mut self, index: &HashMap<(u32, u32), usize>, key: (u32, u32)) {
+        if let Some(&i) = index.get(&key) {
+            self.nodes[i].closed_at = Some(self.expansions);
+        }
+        self.expansions += 1;
+    }
+}
+
+#[derive(Clone)]
 pub struct AStarStrategy {
     pub step_size: f32,
+    pub record_search: bool,
+    pub agent_radius: f32,
+    pub clearance_cost: ClearanceCost,
     grid: Arc<Mutex<Grid>>,
+    search_trace: Option<SearchTrace>,
+    cancel_flag: Option<Arc<AtomicBool>>,
 }
 
 impl AStarStrategy {
     pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
         Self {
             step_size: 20.0,
+            record_search: false,
+            agent_radius: 0.0,
+            clearance_cost: ClearanceCost::default(),
             grid,
+            search_trace: None,
+            cancel_flag: None,
         }
     }
 
-    pub fn path_finding(&self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+    /// Once the flag is raised, a running `path_finding` gives up and returns `None`.
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+
+    pub fn search_trace(&self) -> Option<&SearchTrace> {
+        self.search_trace.as_ref()
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
         let start = start?;
         let goal = goal?;
 
@@ -95,20 +167,35 @@ impl AStarStrategy {
         let mut g_costs: HashMap<(u32, u32), f32> = HashMap::new();
         let mut predecessors: HashMap<(u32, u32), Vec2> = HashMap::new();
 
+        let clearance_map = self
+            .clearance_cost
+            .is_enabled()
+            .then(|| ClearanceMap::from_grid(&self.grid.lock().unwrap()));
+
+        let mut trace = self.record_search.then(SearchTrace::default);
+        let mut trace_index: HashMap<(u32, u32), usize> = HashMap::new();
+
         let start_node = Node {
             pos: start,
             pre: None,
             g: 0.0,
             h: Self::heuristic(start, goal),
         };
+        let start_key = (start.x.to_bits(), start.y.to_bits());
+        if let Some(trace) = trace.as_mut() {
+            trace.open(&mut trace_index, start_key, &start_node);
+        }
         queue.push(start_node);
 
-        let start_key = (start.x.to_bits(), start.y.to_bits());
         g_costs.insert(start_key, 0.0);
 
         let mut goal_node: Option<Node> = None;
 
         while let Some(cur) = queue.pop() {
+            if self.is_cancelled() {
+                return None;
+            }
+
             let cur_key = (cur.pos.x.to_bits(), cur.pos.y.to_bits());
 
             if let Some(&best_g) = g_costs.get(&cur_key) {
@@ -117,6 +204,10 @@ impl AStarStrategy {
                 }
             }
 
+            if let Some(trace) = trace.as_mut() {
+                trace.close(&trace_index, cur_key);
+            }
+
             if self.has_sight(cur.pos, goal) {
                 let goal_key = (goal.x.to_bits(), goal.y.to_bits());
                 predecessors.insert(goal_key, cur.pos);
@@ -131,7 +222,13 @@ impl AStarStrategy {
 
             for dir in ProbeDirection::iter() {
                 if let Some(new_pos) = self.get_new_pos(cur.pos, *dir) {
-                    let new_dist = new_pos.distance(cur.pos);
+                    let mut new_dist = new_pos.distance(cur.pos);
+                    if let Some(map) = clearance_map.as_ref() {
+                        new_dist *= 1.0
+                            + self
+                                .clearance_cost
+                                .penalty(map.at(new_pos) - self.agent_radius);
+                    }
                     let new_g = cur.g + new_dist;
                     let new_key = (new_pos.x.to_bits(), new_pos.y.to_bits());
 
@@ -147,6 +244,9 @@ impl AStarStrategy {
                             h: Self::heuristic(new_pos, goal),
                         };
 
+                        if let Some(trace) = trace.as_mut() {
+                            trace.open(&mut trace_index, new_key, &new_node);
+                        }
                         queue.push(new_node);
                         g_costs.insert(new_key, new_g);
                         predecessors.insert(new_key, cur.pos);
@@ -155,6 +255,8 @@ impl AStarStrategy {
             }
         }
 
+        self.search_trace = trace;
+
         if let Some(goal) = goal_node {
             let mut path = Vec::new();
             let mut current = goal.pos;
@@ -182,23 +284,12 @@ impl AStarStrategy {
         a.distance(b)
     }
 
+    /// Line of sight for a circle of `agent_radius`; a plain ray when the radius is zero.
     fn has_sight(&self, from: Vec2, to: Vec2) -> bool {
-        let direction = (to - from).normalize_or_zero();
-        let distance = from.distance(to);
-        if distance == 0.0 {
-            return true;
-        }
-
-        let ray = Ray {
-            root: from,
-            dir: direction,
-        };
-
         self.grid
             .lock()
             .unwrap()
-            .raycast(ray)
-            .map_or(true, |hit| hit.dist >= distance)
+            .is_segment_clear(from, to, self.agent_radius)
     }
 
     fn get_new_pos(&self, root: Vec2, direction: ProbeDirection) -> Option<Vec2> {
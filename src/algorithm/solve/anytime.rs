@@ -0,0 +1,51 @@
+/// Tracks the best length an anytime solver reports per iteration and decides when
+/// further iterations stop paying off.
+#[derive(Debug, Clone)]
+pub struct StoppingCriterion {
+    pub patience: u64,
+    pub target_len: Option<f64>,
+    best_len: f64,
+    stale_iterations: u64,
+}
+
+impl StoppingCriterion {
+    pub fn new(patience: u64, target_len: Option<f64>) -> Self {
+        Self {
+            patience,
+            target_len,
+            best_len: f64::INFINITY,
+            stale_iterations: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.best_len = f64::INFINITY;
+        self.stale_iterations = 0;
+    }
+
+    pub fn stale_iterations(&self) -> u64 {
+        self.stale_iterations
+    }
+
+    /// Feeds the solver's best length after one iteration and returns whether it converged.
+    pub fn update(&mut self, best_len: f64) -> bool {
+        if best_len < self.best_len - 1e-6 {
+            self.best_len = best_len;
+            self.stale_iterations = 0;
+        } else if best_len > self.best_len {
+            // The best only gets worse when the solver was reset, so start counting again.
+            self.best_len = best_len;
+            self.stale_iterations = 0;
+        } else if self.best_len.is_finite() {
+            // Without any path yet there is nothing to converge on.
+            self.stale_iterations += 1;
+        }
+
+        self.is_converged()
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.target_len.is_some_and(|x| self.best_len <= x)
+            || self.stale_iterations >= self.patience
+    }
+}
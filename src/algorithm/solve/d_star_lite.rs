@@ -0,0 +1,358 @@
+use crate::algorithm::grid::Grid;
+use bevy::prelude::*;
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
+};
+
+/// Priority of a queued cell: `[min(g, rhs) + h + km, min(g, rhs)]`, compared lexicographically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Key(f32, f32);
+
+impl Key {
+    fn less_than(self, other: Key) -> bool {
+        self.0 < other.0 || (self.0 == other.0 && self.1 < other.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueueEntry {
+    key: Key,
+    cell: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.cell == other.cell
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .0
+            .total_cmp(&self.key.0)
+            .then_with(|| other.key.1.total_cmp(&self.key.1))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Incremental planner over the grid cells (Koenig & Likhachev's D* Lite).
+///
+/// The search runs from the goal towards the start, so moving the start or editing walls
+/// only repairs the costs that changed instead of searching again. Cells closer than
+/// `agent_radius` to a wall count as blocked.
+#[derive(Clone)]
+pub struct DStarLiteStrategy {
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    root: Vec2,
+    radius: f32,
+    walls: Vec<bool>,
+    g: Vec<f32>,
+    rhs: Vec<f32>,
+    queue: BinaryHeap<QueueEntry>,
+    queued: Vec<Option<Key>>,
+    km: f32,
+    start: Option<usize>,
+    goal: Option<usize>,
+    revision: u64,
+    expansions: usize,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl DStarLiteStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            agent_radius: 0.0,
+            grid,
+            width: 0,
+            height: 0,
+            cell_size: 0.0,
+            root: Vec2::ZERO,
+            radius: 0.0,
+            walls: Vec::new(),
+            g: Vec::new(),
+            rhs: Vec::new(),
+            queue: BinaryHeap::new(),
+            queued: Vec::new(),
+            km: 0.0,
+            start: None,
+            goal: None,
+            revision: 0,
+            expansions: 0,
+            cancel_flag: None,
+        }
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    /// Cells expanded by the last call to `path_finding`.
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        self.expansions = 0;
+
+        let grid = self.grid.clone();
+        let grid = grid.lock().unwrap();
+        let start_cell = grid.get_from_world_pos(start)?;
+        let goal_cell = grid.get_from_world_pos(goal)?;
+        let start_cell = start_cell.1 * grid.width() + start_cell.0;
+        let goal_cell = goal_cell.1 * grid.width() + goal_cell.0;
+
+        let changes = grid.changes_since(self.revision);
+        let reusable = self.goal == Some(goal_cell)
+            && self.width == grid.width()
+            && self.height == grid.height()
+            && self.radius == self.agent_radius
+            && changes.is_some();
+
+        if !reusable {
+            self.initialize(&grid, start_cell, goal_cell);
+        } else {
+            if let Some(last_start) = self.start {
+                self.km += self.heuristic(last_start, start_cell);
+            }
+            self.start = Some(start_cell);
+
+            // A wall change also blocks or frees the cells within `radius` of it.
+            let reach = (self.radius / self.cell_size).ceil() as usize;
+            let mut touched = Vec::new();
+            for change in changes.unwrap_or_default() {
+                let (x0, y0) = (change.x.saturating_sub(reach), change.y.saturating_sub(reach));
+                let x1 = (change.x + reach).min(self.width - 1);
+                let y1 = (change.y + reach).min(self.height - 1);
+                touched.extend((y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y))));
+            }
+            touched.sort_unstable();
+            touched.dedup();
+            for &(x, y) in touched.iter() {
+                self.walls[y * self.width + x] = self.is_blocked(&grid, x, y);
+            }
+            // A blocked cell alters every edge touching it and the diagonals squeezing past it.
+            for &(x, y) in touched.iter() {
+                for cell in self.block(y * self.width + x) {
+                    self.update_vertex(cell);
+                }
+            }
+        }
+        self.revision = grid.revision();
+        drop(grid);
+
+        if self.walls[start_cell] || self.walls[goal_cell] {
+            return None;
+        }
+        if !self.compute_shortest_path() {
+            return None;
+        }
+
+        self.extract_path(start, goal)
+    }
+
+    fn initialize(&mut self, grid: &Grid, start: usize, goal: usize) {
+        self.width = grid.width();
+        self.height = grid.height();
+        self.cell_size = grid.cell_size();
+        self.root = grid.root();
+        self.radius = self.agent_radius;
+
+        let count = self.width * self.height;
+        self.walls = (0..count)
+            .map(|i| self.is_blocked(grid, i % self.width, i / self.width))
+            .collect();
+        self.g = vec![f32::INFINITY; count];
+        self.rhs = vec![f32::INFINITY; count];
+        self.queue.clear();
+        self.queued = vec![None; count];
+        self.km = 0.0;
+        self.start = Some(start);
+        self.goal = Some(goal);
+
+        self.rhs[goal] = 0.0;
+        let key = self.calculate_key(goal);
+        self.push(goal, key);
+    }
+
+    /// Walls, and with a radius the cells whose center lies closer than it to a wall.
+    fn is_blocked(&self, grid: &Grid, x: usize, y: usize) -> bool {
+        grid.is_wall(x, y) == Some(true)
+            || (self.radius > 0.0 && grid.distance_to_obstacle(self.cell_center(y * self.width + x)) < self.radius)
+    }
+
+    /// Returns false when cancelled before the start's cost settled.
+    fn compute_shortest_path(&mut self) -> bool {
+        let Some(start) = self.start else {
+            return false;
+        };
+
+        while let Some(top) = self.top() {
+            if !top.key.less_than(self.calculate_key(start)) && self.rhs[start] == self.g[start] {
+                break;
+            }
+            if self.is_cancelled() {
+                return false;
+            }
+
+            let cell = top.cell;
+            self.queue.pop();
+            self.remove(cell);
+            let new_key = self.calculate_key(cell);
+            self.expansions += 1;
+
+            if top.key.less_than(new_key) {
+                self.push(cell, new_key);
+            } else if self.g[cell] > self.rhs[cell] {
+                self.g[cell] = self.rhs[cell];
+                for neighbour in self.neighbours(cell) {
+                    self.update_vertex(neighbour);
+                }
+            } else {
+                self.g[cell] = f32::INFINITY;
+                self.update_vertex(cell);
+                for neighbour in self.neighbours(cell) {
+                    self.update_vertex(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    fn update_vertex(&mut self, cell: usize) {
+        if Some(cell) != self.goal {
+            self.rhs[cell] = self
+                .neighbours(cell)
+                .map(|x| self.cost(cell, x) + self.g[x])
+                .fold(f32::INFINITY, f32::min);
+        }
+        self.remove(cell);
+        if self.g[cell] != self.rhs[cell] {
+            let key = self.calculate_key(cell);
+            self.push(cell, key);
+        }
+    }
+
+    fn extract_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let mut current = self.start?;
+        let goal_cell = self.goal?;
+        if !self.g[current].is_finite() {
+            return None;
+        }
+
+        let mut path = vec![start];
+        for _ in 0..self.width * self.height {
+            if current == goal_cell {
+                path.push(goal);
+                return Some(path);
+            }
+            let (next, cost) = self
+                .neighbours(current)
+                .map(|x| (x, self.cost(current, x) + self.g[x]))
+                .min_by(|a, b| a.1.total_cmp(&b.1))?;
+            if !cost.is_finite() {
+                return None;
+            }
+            if next != goal_cell {
+                path.push(self.cell_center(next));
+            }
+            current = next;
+        }
+        None
+    }
+
+    fn calculate_key(&self, cell: usize) -> Key {
+        let best = self.g[cell].min(self.rhs[cell]);
+        let h = self.start.map_or(0.0, |x| self.heuristic(x, cell));
+        Key(best + h + self.km, best)
+    }
+
+    fn push(&mut self, cell: usize, key: Key) {
+        self.queued[cell] = Some(key);
+        self.queue.push(QueueEntry { key, cell });
+    }
+
+    fn remove(&mut self, cell: usize) {
+        self.queued[cell] = None;
+    }
+
+    /// Drops stale entries until the head matches a live key, then peeks it.
+    fn top(&mut self) -> Option<QueueEntry> {
+        while let Some(entry) = self.queue.peek().copied() {
+            if self.queued[entry.cell] == Some(entry.key) {
+                return Some(entry);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    /// Octile distance between two cells, in world units.
+    fn heuristic(&self, a: usize, b: usize) -> f32 {
+        let dx = (a % self.width).abs_diff(b % self.width) as f32;
+        let dy = (a / self.width).abs_diff(b / self.width) as f32;
+        (dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)) * self.cell_size
+    }
+
+    /// Edge cost between neighbouring cells; diagonals may not cut a wall corner.
+    fn cost(&self, a: usize, b: usize) -> f32 {
+        if self.walls[a] || self.walls[b] {
+            return f32::INFINITY;
+        }
+        let (ax, ay) = (a % self.width, a / self.width);
+        let (bx, by) = (b % self.width, b / self.width);
+        if ax != bx && ay != by {
+            if self.walls[ay * self.width + bx] || self.walls[by * self.width + ax] {
+                return f32::INFINITY;
+            }
+            return std::f32::consts::SQRT_2 * self.cell_size;
+        }
+        self.cell_size
+    }
+
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> + use<> {
+        let (x, y) = ((cell % self.width) as isize, (cell / self.width) as isize);
+        let (width, height) = (self.width as isize, self.height as isize);
+        [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)]
+            .into_iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && nx < width && ny < height)
+            .map(move |(nx, ny)| (ny * width + nx) as usize)
+    }
+
+    /// The cell and its eight neighbours.
+    fn block(&self, cell: usize) -> Vec<usize> {
+        std::iter::once(cell).chain(self.neighbours(cell)).collect()
+    }
+
+    fn cell_center(&self, cell: usize) -> Vec2 {
+        self.root
+            + (Vec2::new((cell % self.width) as f32, (cell / self.width) as f32) + 0.5) * self.cell_size
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
@@ -0,0 +1,349 @@
+use crate::algorithm::{grid::Grid, path::path_length, types::Ray};
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// Added per segment that still crosses a wall after repair.
+const BLOCKED_PENALTY: f32 = 1000.0;
+/// Sideways steps tried when repairing a blocked segment, in cells.
+const REPAIR_STEPS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    pub index: u64,
+    pub best: f32,
+    pub mean: f32,
+    pub feasible: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Individual {
+    /// Interior waypoints; start and goal are implied.
+    waypoints: Vec<Vec2>,
+    fitness: f32,
+    feasible: bool,
+}
+
+/// Genetic algorithm over variable-length waypoint chromosomes. One call to `path_finding`
+/// runs one generation, so the population keeps improving across plans like the colony does.
+/// Segments must stay `agent_radius` clear of the walls to count as feasible.
+#[derive(Clone)]
+pub struct GeneticStrategy {
+    pub population_size: usize,
+    pub elite: usize,
+    pub tournament_size: usize,
+    pub crossover_rate: f64,
+    pub mutation_rate: f64,
+    pub max_waypoints: usize,
+    pub history_size: usize,
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    population: Vec<Individual>,
+    endpoints: Option<(Vec2, Vec2)>,
+    best: Option<Individual>,
+    generation: u64,
+    history: VecDeque<GenerationStats>,
+    seed: Option<u64>,
+    rng: StdRng,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl GeneticStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            population_size: 40,
+            elite: 2,
+            tournament_size: 3,
+            crossover_rate: 0.8,
+            mutation_rate: 0.3,
+            max_waypoints: 12,
+            history_size: 300,
+            agent_radius: 0.0,
+            grid,
+            population: Vec::new(),
+            endpoints: None,
+            best: None,
+            generation: 0,
+            history: VecDeque::new(),
+            seed: None,
+            rng: StdRng::from_os_rng(),
+            cancel_flag: None,
+        }
+    }
+
+    /// Reseeds and starts over from a fresh population when the seed changes.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        if seed != self.seed {
+            self.seed = seed;
+            self.reset();
+        }
+    }
+
+    /// Takes over the settings of `main`, for a GA that plans another leg of the same route.
+    pub fn match_settings(&mut self, main: &GeneticStrategy) {
+        self.population_size = main.population_size;
+        self.elite = main.elite;
+        self.tournament_size = main.tournament_size;
+        self.crossover_rate = main.crossover_rate;
+        self.mutation_rate = main.mutation_rate;
+        self.max_waypoints = main.max_waypoints;
+        self.history_size = main.history_size;
+        self.agent_radius = main.agent_radius;
+        self.set_seed(main.seed);
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    pub fn reset(&mut self) {
+        self.population.clear();
+        self.best = None;
+        self.generation = 0;
+        self.history.clear();
+        self.rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Length of the best feasible path found so far, infinite before the first one.
+    pub fn global_best_len(&self) -> f64 {
+        self.best
+            .as_ref()
+            .filter(|x| x.feasible)
+            .map_or(f64::INFINITY, |x| x.fitness as f64)
+    }
+
+    pub fn history(&self) -> &VecDeque<GenerationStats> {
+        &self.history
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        if self.endpoints != Some((start, goal)) {
+            self.endpoints = Some((start, goal));
+            self.reset();
+        }
+        let grid = self.grid.lock().unwrap().clone();
+
+        if self.population.is_empty() {
+            self.population = (0..self.population_size)
+                .map(|_| {
+                    let count = self.rng.random_range(0..=3);
+                    let waypoints = (0..count).map(|_| self.random_point(&grid)).collect();
+                    self.evaluate(&grid, start, goal, waypoints)
+                })
+                .collect();
+        } else {
+            // Walls may have moved since the last generation.
+            let population = std::mem::take(&mut self.population);
+            self.population = population
+                .into_iter()
+                .map(|x| self.evaluate(&grid, start, goal, x.waypoints))
+                .collect();
+            if let Some(best) = self.best.take() {
+                self.best = Some(self.evaluate(&grid, start, goal, best.waypoints));
+            }
+        }
+
+        self.population.sort_by(|a, b| a.fitness.total_cmp(&b.fitness));
+        let mut next: Vec<Individual> = self.population.iter().take(self.elite).cloned().collect();
+        while next.len() < self.population_size {
+            if self.cancel_flag.as_ref().is_some_and(|x| x.load(Ordering::Relaxed)) {
+                return None;
+            }
+            let first = self.select();
+            let second = self.select();
+            let mut waypoints = if self.rng.random_bool(self.crossover_rate) {
+                self.crossover(&grid, &first.waypoints, &second.waypoints)
+            } else {
+                first.waypoints.clone()
+            };
+            if self.rng.random_bool(self.mutation_rate) {
+                self.mutate(&grid, start, goal, &mut waypoints);
+            }
+            next.push(self.evaluate(&grid, start, goal, waypoints));
+        }
+        self.population = next;
+        self.generation += 1;
+
+        for individual in self.population.iter() {
+            if self.best.as_ref().is_none_or(|x| individual.fitness < x.fitness) {
+                self.best = Some(individual.clone());
+            }
+        }
+        self.record_generation();
+
+        let best = self.best.as_ref().filter(|x| x.feasible)?;
+        Some(
+            std::iter::once(start)
+                .chain(best.waypoints.iter().copied())
+                .chain(std::iter::once(goal))
+                .collect(),
+        )
+    }
+
+    fn record_generation(&mut self) {
+        let fitness: Vec<f32> = self.population.iter().map(|x| x.fitness).collect();
+        let stats = GenerationStats {
+            index: self.generation,
+            best: fitness.iter().copied().fold(f32::INFINITY, f32::min),
+            mean: fitness.iter().sum::<f32>() / fitness.len().max(1) as f32,
+            feasible: self.population.iter().filter(|x| x.feasible).count(),
+        };
+        while self.history.len() >= self.history_size.max(1) {
+            self.history.pop_front();
+        }
+        self.history.push_back(stats);
+    }
+
+    /// Repairs the chromosome, then scores it by length plus a penalty per blocked segment.
+    fn evaluate(&self, grid: &Grid, start: Vec2, goal: Vec2, waypoints: Vec<Vec2>) -> Individual {
+        let mut path: Vec<Vec2> = std::iter::once(start)
+            .chain(waypoints)
+            .chain(std::iter::once(goal))
+            .collect();
+        self.repair(grid, &mut path);
+
+        let blocked = path.windows(2).filter(|x| !self.is_clear(grid, x[0], x[1])).count();
+        Individual {
+            fitness: path_length(&path) + BLOCKED_PENALTY * blocked as f32,
+            feasible: blocked == 0,
+            waypoints: path[1..path.len() - 1].to_vec(),
+        }
+    }
+
+    /// Where a segment hits a wall, inserts a detour point beside the hit, sliding along the wall
+    /// until the first half of the segment is clear. A segment that only grazes a wall within
+    /// `agent_radius` is pushed sideways from its midpoint instead.
+    fn repair(&self, grid: &Grid, path: &mut Vec<Vec2>) {
+        let step = grid.cell_size();
+        let mut i = 0;
+        while i + 1 < path.len() && path.len() < self.max_waypoints + 2 {
+            let (from, to) = (path[i], path[i + 1]);
+            if self.is_clear(grid, from, to) {
+                i += 1;
+                continue;
+            }
+
+            let hit = grid.raycast(Ray {
+                root: from,
+                dir: to - from,
+            });
+            let (base, tangent) = match hit.filter(|x| x.dist < from.distance(to)) {
+                Some(hit) => (
+                    hit.pt + hit.nor * (step * 0.5 + self.agent_radius),
+                    Vec2::new(-hit.nor.y, hit.nor.x),
+                ),
+                // Only clearance is missing: step off the nearest wall the way a hit would.
+                None => {
+                    let mid = (from + to) / 2.0;
+                    let away = grid.distance_gradient(mid);
+                    match grid.nearest_obstacle(mid).filter(|_| away != Vec2::ZERO) {
+                        Some(wall) => (wall + away * (step * 0.5 + self.agent_radius), away.perp()),
+                        None => (mid, (to - from).normalize_or_zero().perp()),
+                    }
+                }
+            };
+            let detour = (1..=REPAIR_STEPS)
+                .flat_map(|k| [k as f32, -(k as f32)])
+                .map(|k| base + tangent * k * step)
+                .find(|x| self.is_free(grid, *x) && self.is_clear(grid, from, *x));
+            match detour {
+                Some(point) => path.insert(i + 1, point),
+                None => i += 1,
+            }
+            i += 1;
+        }
+    }
+
+    fn select(&mut self) -> Individual {
+        let mut best: Option<&Individual> = None;
+        for _ in 0..self.tournament_size.max(1) {
+            let candidate = &self.population[self.rng.random_range(0..self.population.len())];
+            if best.is_none_or(|x| candidate.fitness < x.fitness) {
+                best = Some(candidate);
+            }
+        }
+        best.unwrap().clone()
+    }
+
+    /// Joins the head of `first` to the tail of `second` where both pass through the same cell.
+    fn crossover(&mut self, grid: &Grid, first: &[Vec2], second: &[Vec2]) -> Vec<Vec2> {
+        let cell = |x: Vec2| grid.get_from_world_pos(x);
+        let shared: Vec<(usize, usize)> = (0..first.len())
+            .flat_map(|i| (0..second.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| cell(first[i]).is_some() && cell(first[i]) == cell(second[j]))
+            .collect();
+        if shared.is_empty() {
+            return first.to_vec();
+        }
+
+        let (i, j) = shared[self.rng.random_range(0..shared.len())];
+        let mut child: Vec<Vec2> = first[..=i].to_vec();
+        child.extend_from_slice(&second[j + 1..]);
+        child.truncate(self.max_waypoints);
+        child
+    }
+
+    fn mutate(&mut self, grid: &Grid, start: Vec2, goal: Vec2, waypoints: &mut Vec<Vec2>) {
+        let step = grid.cell_size();
+        match self.rng.random_range(0..3) {
+            0 if waypoints.len() < self.max_waypoints => {
+                let index = self.rng.random_range(0..=waypoints.len());
+                let from = if index == 0 { start } else { waypoints[index - 1] };
+                let to = waypoints.get(index).copied().unwrap_or(goal);
+                let offset = Vec2::new(self.rng.random_range(-1.0..1.0), self.rng.random_range(-1.0..1.0)) * step * 2.0;
+                waypoints.insert(index, (from + to) / 2.0 + offset);
+            }
+            1 if !waypoints.is_empty() => {
+                let index = self.rng.random_range(0..waypoints.len());
+                waypoints.remove(index);
+            }
+            _ if !waypoints.is_empty() => {
+                let index = self.rng.random_range(0..waypoints.len());
+                let offset = Vec2::new(self.rng.random_range(-1.0..1.0), self.rng.random_range(-1.0..1.0)) * step;
+                let moved = waypoints[index] + offset;
+                if self.is_free(grid, moved) {
+                    waypoints[index] = moved;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A random air point, or the grid center when none turns up.
+    fn random_point(&mut self, grid: &Grid) -> Vec2 {
+        let size = Vec2::new(grid.world_width(), grid.world_height());
+        for _ in 0..1000 {
+            let pos = grid.root() + Vec2::new(self.rng.random_range(0.0..size.x), self.rng.random_range(0.0..size.y));
+            if self.is_free(grid, pos) {
+                return pos;
+            }
+        }
+        grid.root() + size / 2.0
+    }
+
+    fn is_free(&self, grid: &Grid, pos: Vec2) -> bool {
+        grid.get_from_world_pos(pos)
+            .is_some_and(|(x, y)| grid.is_air(x, y) == Some(true))
+            && grid.distance_to_obstacle(pos) >= self.agent_radius
+    }
+
+    fn is_clear(&self, grid: &Grid, from: Vec2, to: Vec2) -> bool {
+        grid.is_segment_clear(from, to, self.agent_radius)
+    }
+}
@@ -0,0 +1,688 @@
+use crate::algorithm::{
+    grid::{CellChange, Grid, GridCell},
+    path::path_length,
+    solve::a_star::AStarStrategy,
+};
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
+    time::Instant,
+};
+
+type Cell = (usize, usize);
+
+/// Entrances at least this wide get a transition at each end instead of one in the middle.
+const WIDE_ENTRANCE: usize = 6;
+
+/// How the last query got its abstract graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HpaUpdate {
+    Built { clusters: usize },
+    Rebuilt { clusters: usize },
+    Reused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Side {
+    Right,
+    Top,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Cluster {
+    nodes: Vec<Cell>,
+    edges: HashMap<Cell, Vec<(Cell, f32)>>,
+    /// Cell path for every intra-cluster edge, keyed by its endpoints.
+    paths: HashMap<(Cell, Cell), Vec<Cell>>,
+}
+
+#[derive(Debug, Clone)]
+struct AbstractGraph {
+    cluster_size: usize,
+    agent_radius: f32,
+    columns: usize,
+    rows: usize,
+    clusters: Vec<Cluster>,
+    /// Transition cells and the cells facing them across a cluster border.
+    entrances: HashMap<Cell, Vec<Cell>>,
+    revision: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueueEntry {
+    cost: f32,
+    cell: Cell,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost && self.cell == other.cell
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra confined to one cluster's cells.
+struct LocalSearch {
+    min: Cell,
+    width: usize,
+    cost: Vec<f32>,
+    parent: Vec<Option<Cell>>,
+}
+
+impl LocalSearch {
+    fn run(grid: &Grid, radius: f32, (min, max): (Cell, Cell), from: Cell) -> Self {
+        let width = max.0 - min.0;
+        let mut search = Self {
+            min,
+            width,
+            cost: vec![f32::INFINITY; width * (max.1 - min.1)],
+            parent: vec![None; width * (max.1 - min.1)],
+        };
+        let inside = |(x, y): Cell| x >= min.0 && y >= min.1 && x < max.0 && y < max.1;
+
+        let index = search.index(from);
+        search.cost[index] = 0.0;
+        let mut open = BinaryHeap::new();
+        open.push(QueueEntry { cost: 0.0, cell: from });
+        while let Some(QueueEntry { cost, cell }) = open.pop() {
+            if cost > search.cost[search.index(cell)] {
+                continue;
+            }
+            for (next, step) in neighbours(grid, radius, cell).filter(|x| inside(x.0)) {
+                let next_cost = cost + step * grid.cell_size();
+                let index = search.index(next);
+                if next_cost < search.cost[index] {
+                    search.cost[index] = next_cost;
+                    search.parent[index] = Some(cell);
+                    open.push(QueueEntry { cost: next_cost, cell: next });
+                }
+            }
+        }
+        search
+    }
+
+    fn index(&self, (x, y): Cell) -> usize {
+        (y - self.min.1) * self.width + (x - self.min.0)
+    }
+
+    fn cost(&self, cell: Cell) -> f32 {
+        self.cost[self.index(cell)]
+    }
+
+    /// Cells from the search origin to `to`, both included.
+    fn path_to(&self, to: Cell) -> Vec<Cell> {
+        let mut path = vec![to];
+        while let Some(parent) = self.parent[self.index(path[path.len() - 1])] {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Hierarchical A* over square clusters of cells. Entrances between clusters and the shortest
+/// paths inside each cluster are computed once; a query only searches that abstract graph and
+/// splices in the cached cell paths. Wall edits rebuild just the clusters around them. Cells
+/// closer than `agent_radius` to a wall count as blocked.
+#[derive(Clone)]
+pub struct HpaStrategy {
+    pub cluster_size: usize,
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    graph: Option<AbstractGraph>,
+    last_update: Option<HpaUpdate>,
+    expansions: usize,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl HpaStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            cluster_size: 5,
+            agent_radius: 0.0,
+            grid,
+            graph: None,
+            last_update: None,
+            expansions: 0,
+            cancel_flag: None,
+        }
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    pub fn last_update(&self) -> Option<HpaUpdate> {
+        self.last_update
+    }
+
+    /// Abstract nodes expanded by the last query.
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// Abstract nodes and undirected edges, entrances and intra-cluster paths together.
+    pub fn graph_size(&self) -> (usize, usize) {
+        self.graph.as_ref().map_or((0, 0), |graph| {
+            let intra: usize = graph.clusters.iter().map(|x| x.paths.len()).sum();
+            let inter: usize = graph.entrances.values().map(|x| x.len()).sum();
+            (graph.entrances.len(), (intra + inter) / 2)
+        })
+    }
+
+    pub fn graph_edges(&self) -> Vec<(Vec2, Vec2)> {
+        let grid = self.grid.lock().unwrap();
+        let Some(graph) = self.graph.as_ref() else {
+            return Vec::new();
+        };
+        let intra = graph.clusters.iter().flat_map(|x| x.paths.keys());
+        let inter = graph.entrances.iter().flat_map(|(a, x)| x.iter().map(move |b| (*a, *b)));
+        intra
+            .copied()
+            .chain(inter)
+            .filter(|(a, b)| a < b)
+            .map(|(a, b)| (cell_center(&grid, a), cell_center(&grid, b)))
+            .collect()
+    }
+
+    /// Cluster outlines, for drawing.
+    pub fn cluster_bounds(&self) -> Vec<(Vec2, Vec2)> {
+        let grid = self.grid.lock().unwrap();
+        let Some(graph) = self.graph.as_ref() else {
+            return Vec::new();
+        };
+        (0..graph.columns * graph.rows)
+            .map(|i| {
+                let (min, max) = graph.bounds(&grid, (i % graph.columns, i / graph.columns));
+                let corner = |(x, y): Cell| grid.root() + Vec2::new(x as f32, y as f32) * grid.cell_size();
+                (corner(min), corner(max))
+            })
+            .collect()
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        let grid = self.grid.lock().unwrap().clone();
+        self.update_graph(&grid)?;
+
+        let graph = self.graph.as_ref()?;
+        let cells = graph.query(&grid, grid.get_from_world_pos(start)?, grid.get_from_world_pos(goal)?);
+        let (cells, expansions) = cells?;
+        self.expansions = expansions;
+        Some(world_path(&grid, start, goal, &cells))
+    }
+
+    /// Brings the abstract graph up to the grid's revision, rebuilding as little as possible.
+    fn update_graph(&mut self, grid: &Grid) -> Option<()> {
+        let changes = self
+            .graph
+            .as_ref()
+            .filter(|x| x.cluster_size == self.cluster_size.max(1) && x.agent_radius == self.agent_radius)
+            .and_then(|x| grid.changes_since(x.revision));
+        let mut graph = match (self.graph.take(), changes) {
+            (Some(graph), Some(changes)) if changes.is_empty() => {
+                self.last_update = Some(HpaUpdate::Reused);
+                graph
+            }
+            (Some(mut graph), Some(changes)) => {
+                let clusters = self.rebuild(grid, &mut graph, &changes)?;
+                self.last_update = Some(HpaUpdate::Rebuilt { clusters });
+                graph
+            }
+            _ => {
+                let graph = self.build(grid)?;
+                self.last_update = Some(HpaUpdate::Built {
+                    clusters: graph.clusters.len(),
+                });
+                graph
+            }
+        };
+        graph.revision = grid.revision();
+        self.graph = Some(graph);
+        Some(())
+    }
+
+    fn build(&self, grid: &Grid) -> Option<AbstractGraph> {
+        let cluster_size = self.cluster_size.max(1);
+        let columns = grid.width().div_ceil(cluster_size);
+        let rows = grid.height().div_ceil(cluster_size);
+        let mut graph = AbstractGraph {
+            cluster_size,
+            agent_radius: self.agent_radius,
+            columns,
+            rows,
+            clusters: vec![Cluster::default(); columns * rows],
+            entrances: HashMap::new(),
+            revision: grid.revision(),
+        };
+
+        for cy in 0..rows {
+            for cx in 0..columns {
+                for side in [Side::Right, Side::Top] {
+                    graph.link_border(grid, (cx, cy), side);
+                }
+            }
+        }
+        for cluster in 0..columns * rows {
+            if self.is_cancelled() {
+                return None;
+            }
+            graph.build_cluster(grid, (cluster % columns, cluster / columns));
+        }
+        Some(graph)
+    }
+
+    /// Relinks every border of the clusters holding changed cells, then recomputes those clusters
+    /// and their neighbours, whose entrances may have moved. A changed wall also blocks or frees
+    /// the cells within `agent_radius` of it. Returns how many clusters were recomputed.
+    fn rebuild(&self, grid: &Grid, graph: &mut AbstractGraph, changes: &[CellChange]) -> Option<usize> {
+        let reach = (graph.agent_radius / grid.cell_size()).ceil() as usize;
+        let mut dirty: HashSet<Cell> = HashSet::new();
+        for change in changes {
+            let (x0, y0) = (change.x.saturating_sub(reach), change.y.saturating_sub(reach));
+            let x1 = (change.x + reach).min(grid.width().saturating_sub(1));
+            let y1 = (change.y + reach).min(grid.height().saturating_sub(1));
+            let ((cx0, cy0), (cx1, cy1)) = (graph.cluster_of((x0, y0)), graph.cluster_of((x1, y1)));
+            dirty.extend((cy0..=cy1).flat_map(|cy| (cx0..=cx1).map(move |cx| (cx, cy))));
+        }
+
+        let mut borders = HashSet::new();
+        let mut affected = HashSet::new();
+        for &(cx, cy) in dirty.iter() {
+            borders.insert(((cx, cy), Side::Right));
+            borders.insert(((cx, cy), Side::Top));
+            if cx > 0 {
+                borders.insert(((cx - 1, cy), Side::Right));
+            }
+            if cy > 0 {
+                borders.insert(((cx, cy - 1), Side::Top));
+            }
+            affected.extend(graph.around((cx, cy)));
+        }
+
+        for &(cluster, side) in borders.iter() {
+            graph.clear_border(grid, cluster, side);
+            graph.link_border(grid, cluster, side);
+        }
+        for &cluster in affected.iter() {
+            if self.is_cancelled() {
+                return None;
+            }
+            graph.build_cluster(grid, cluster);
+        }
+        Some(affected.len())
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
+
+impl AbstractGraph {
+    fn index(&self, (cx, cy): Cell) -> usize {
+        cy * self.columns + cx
+    }
+
+    /// Lower (inclusive) and upper (exclusive) cell corners of a cluster.
+    fn bounds(&self, grid: &Grid, (cx, cy): Cell) -> (Cell, Cell) {
+        let min = (cx * self.cluster_size, cy * self.cluster_size);
+        let max = (
+            (min.0 + self.cluster_size).min(grid.width()),
+            (min.1 + self.cluster_size).min(grid.height()),
+        );
+        (min, max)
+    }
+
+    fn cluster_of(&self, (x, y): Cell) -> Cell {
+        (x / self.cluster_size, y / self.cluster_size)
+    }
+
+    /// A cluster and its four direct neighbours that exist.
+    fn around(&self, (cx, cy): Cell) -> Vec<Cell> {
+        let mut clusters = vec![(cx, cy)];
+        if cx > 0 {
+            clusters.push((cx - 1, cy));
+        }
+        if cy > 0 {
+            clusters.push((cx, cy - 1));
+        }
+        if cx + 1 < self.columns {
+            clusters.push((cx + 1, cy));
+        }
+        if cy + 1 < self.rows {
+            clusters.push((cx, cy + 1));
+        }
+        clusters
+    }
+
+    /// Facing cell pairs across the right or top border of a cluster; empty at the grid's edge.
+    fn border_pairs(&self, grid: &Grid, cluster: Cell, side: Side) -> Vec<(Cell, Cell)> {
+        let (min, max) = self.bounds(grid, cluster);
+        match side {
+            Side::Right if max.0 < grid.width() => (min.1..max.1).map(|y| ((max.0 - 1, y), (max.0, y))).collect(),
+            Side::Top if max.1 < grid.height() => (min.0..max.0).map(|x| ((x, max.1 - 1), (x, max.1))).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Splits the open stretch of a border into entrances and adds their transitions.
+    fn link_border(&mut self, grid: &Grid, cluster: Cell, side: Side) {
+        let is_open = |cell: Cell| is_open(grid, self.agent_radius, cell);
+        let pairs = self.border_pairs(grid, cluster, side);
+        let mut run: Vec<(Cell, Cell)> = Vec::new();
+        for pair in pairs.into_iter().map(Some).chain(std::iter::once(None)) {
+            match pair {
+                Some(pair) if is_open(pair.0) && is_open(pair.1) => run.push(pair),
+                _ => {
+                    let transitions = match run.len() {
+                        0 => vec![],
+                        n if n < WIDE_ENTRANCE => vec![run[n / 2]],
+                        n => vec![run[0], run[n - 1]],
+                    };
+                    for (a, b) in transitions {
+                        self.entrances.entry(a).or_default().push(b);
+                        self.entrances.entry(b).or_default().push(a);
+                    }
+                    run.clear();
+                }
+            }
+        }
+    }
+
+    fn clear_border(&mut self, grid: &Grid, cluster: Cell, side: Side) {
+        for (a, b) in self.border_pairs(grid, cluster, side) {
+            for (from, to) in [(a, b), (b, a)] {
+                if let Some(links) = self.entrances.get_mut(&from) {
+                    links.retain(|x| *x != to);
+                    if links.is_empty() {
+                        self.entrances.remove(&from);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Connects every pair of transitions inside a cluster by their shortest local path.
+    fn build_cluster(&mut self, grid: &Grid, cluster: Cell) {
+        let bounds = self.bounds(grid, cluster);
+        let (min, max) = bounds;
+        // Transitions only ever sit on the cluster's rim.
+        let mut nodes: Vec<Cell> = (min.1..max.1)
+            .flat_map(|y| (min.0..max.0).map(move |x| (x, y)))
+            .filter(|&(x, y)| x == min.0 || y == min.1 || x + 1 == max.0 || y + 1 == max.1)
+            .filter(|x| self.entrances.contains_key(x))
+            .collect();
+        nodes.sort();
+
+        let mut built = Cluster::default();
+        for &from in nodes.iter() {
+            let search = LocalSearch::run(grid, self.agent_radius, bounds, from);
+            for &to in nodes.iter().filter(|x| **x != from) {
+                let cost = search.cost(to);
+                if cost.is_finite() {
+                    built.edges.entry(from).or_default().push((to, cost));
+                    built.paths.insert((from, to), search.path_to(to));
+                }
+            }
+        }
+        built.nodes = nodes;
+        let index = self.index(cluster);
+        self.clusters[index] = built;
+    }
+
+    /// A* over the transitions, with start and goal linked into their clusters for this query
+    /// only. Returns the refined cell path and how many abstract nodes were expanded.
+    fn query(&self, grid: &Grid, start: Cell, goal: Cell) -> Option<(Vec<Cell>, usize)> {
+        if !is_open(grid, self.agent_radius, start) || !is_open(grid, self.agent_radius, goal) {
+            return None;
+        }
+
+        let radius = self.agent_radius;
+        let start_search = LocalSearch::run(grid, radius, self.bounds(grid, self.cluster_of(start)), start);
+        let goal_search = LocalSearch::run(grid, radius, self.bounds(grid, self.cluster_of(goal)), goal);
+        let links = |search: &LocalSearch, cluster: Cell| -> Vec<(Cell, f32)> {
+            self.clusters[self.index(cluster)]
+                .nodes
+                .iter()
+                .map(|x| (*x, search.cost(*x)))
+                .filter(|x| x.1.is_finite())
+                .collect()
+        };
+        let mut start_links = links(&start_search, self.cluster_of(start));
+        // Sharing a cluster, the local path competes with any way round through the neighbours.
+        if self.cluster_of(start) == self.cluster_of(goal) && start_search.cost(goal).is_finite() {
+            start_links.push((goal, start_search.cost(goal)));
+        }
+        let goal_links: HashMap<Cell, f32> = links(&goal_search, self.cluster_of(goal)).into_iter().collect();
+
+        let heuristic = |(x, y): Cell| {
+            let (dx, dy) = (x.abs_diff(goal.0) as f32, y.abs_diff(goal.1) as f32);
+            (dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)) * grid.cell_size()
+        };
+
+        let mut cost: HashMap<Cell, f32> = HashMap::from([(start, 0.0)]);
+        let mut parent: HashMap<Cell, Cell> = HashMap::new();
+        let mut open = BinaryHeap::from([QueueEntry {
+            cost: heuristic(start),
+            cell: start,
+        }]);
+        let mut expansions = 0;
+        while let Some(QueueEntry { cell, cost: f }) = open.pop() {
+            let g = cost[&cell];
+            if f > g + heuristic(cell) {
+                continue;
+            }
+            if cell == goal {
+                let mut abstract_path = vec![goal];
+                while let Some(previous) = parent.get(&abstract_path[abstract_path.len() - 1]) {
+                    abstract_path.push(*previous);
+                }
+                abstract_path.reverse();
+                return Some((self.refine(&abstract_path, &start_search, &goal_search), expansions));
+            }
+            expansions += 1;
+
+            let intra = if cell == start {
+                start_links.clone()
+            } else {
+                let cluster = &self.clusters[self.index(self.cluster_of(cell))];
+                cluster.edges.get(&cell).cloned().unwrap_or_default()
+            };
+            let inter = self
+                .entrances
+                .get(&cell)
+                .into_iter()
+                .flatten()
+                .map(|x| (*x, grid.cell_size()));
+            let to_goal = goal_links.get(&cell).map(|x| (goal, *x));
+            for (next, length) in intra.into_iter().chain(inter).chain(to_goal) {
+                let next_cost = g + length;
+                if cost.get(&next).is_none_or(|x| next_cost < *x) {
+                    cost.insert(next, next_cost);
+                    parent.insert(next, cell);
+                    open.push(QueueEntry {
+                        cost: next_cost + heuristic(next),
+                        cell: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Expands abstract steps back into cells using the cached and per-query local paths.
+    fn refine(&self, abstract_path: &[Cell], start_search: &LocalSearch, goal_search: &LocalSearch) -> Vec<Cell> {
+        let (start, goal) = (abstract_path[0], abstract_path[abstract_path.len() - 1]);
+        let mut cells = vec![start];
+        for (i, step) in abstract_path.windows(2).enumerate() {
+            let (from, to) = (step[0], step[1]);
+            let segment = if i == 0 && from == start && self.cluster_of(to) == self.cluster_of(start) {
+                start_search.path_to(to)
+            } else if to == goal && self.cluster_of(from) == self.cluster_of(goal) {
+                let mut path = goal_search.path_to(from);
+                path.reverse();
+                path
+            } else {
+                let cluster = &self.clusters[self.index(self.cluster_of(from))];
+                cluster.paths.get(&(from, to)).cloned().unwrap_or(vec![from, to])
+            };
+            cells.extend(segment.into_iter().skip(1));
+        }
+        cells
+    }
+}
+
+/// Air cells an agent of `radius` fits on, its center kept that far from every wall.
+fn is_open(grid: &Grid, radius: f32, (x, y): Cell) -> bool {
+    grid.is_air(x, y) == Some(true)
+        && (radius <= 0.0 || grid.distance_to_obstacle(cell_center(grid, (x, y))) >= radius)
+}
+
+/// Open 8-connected neighbours with their step length in cells; diagonals never cut a blocked corner.
+fn neighbours(grid: &Grid, radius: f32, (x, y): Cell) -> impl Iterator<Item = (Cell, f32)> + '_ {
+    let is_air = move |nx: isize, ny: isize| nx >= 0 && ny >= 0 && is_open(grid, radius, (nx as usize, ny as usize));
+    let (x, y) = (x as isize, y as isize);
+    [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)]
+        .into_iter()
+        .filter(move |&(dx, dy)| {
+            is_air(x + dx, y + dy) && (dx == 0 || dy == 0 || (is_air(x + dx, y) && is_air(x, y + dy)))
+        })
+        .map(move |(dx, dy)| {
+            let step = if dx != 0 && dy != 0 { std::f32::consts::SQRT_2 } else { 1.0 };
+            (((x + dx) as usize, (y + dy) as usize), step)
+        })
+}
+
+fn cell_center(grid: &Grid, (x, y): Cell) -> Vec2 {
+    grid.root() + (Vec2::new(x as f32, y as f32) + 0.5) * grid.cell_size()
+}
+
+/// The exact start and goal joined by the centers of the cells between them.
+fn world_path(grid: &Grid, start: Vec2, goal: Vec2, cells: &[Cell]) -> Vec<Vec2> {
+    let interior = cells.get(1..cells.len().saturating_sub(1)).unwrap_or_default();
+    let mut path = vec![start];
+    path.extend(interior.iter().map(|x| cell_center(grid, *x)));
+    path.push(goal);
+    path
+}
+
+/// Timings of HPA* against flat A* on one generated map.
+#[derive(Debug, Clone, Copy)]
+pub struct HpaBenchmark {
+    pub size: usize,
+    pub a_star_ms: f64,
+    pub a_star_len: Option<f32>,
+    pub build_ms: f64,
+    pub query_ms: f64,
+    pub hpa_len: Option<f32>,
+    pub rebuild_ms: f64,
+    pub rebuilt_clusters: usize,
+}
+
+impl HpaBenchmark {
+    /// A square map of `size` cells with random wall blocks, solved corner to corner by both
+    /// solvers; then one wall is toggled to time the incremental rebuild.
+    pub fn run(size: usize, cluster_size: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let start = Vec2::splat(30.0);
+        let goal = Vec2::splat(size as f32 * 60.0 - 30.0);
+        // Large maps may wall a corner in; draw again so both solvers have a path to find.
+        let grid = loop {
+            let grid = Self::generate(size, &mut rng);
+            if grid.geodesic_field(goal).and_then(|x| x.distance_at(start)).is_some() {
+                break grid;
+            }
+        };
+        let grid = Arc::new(Mutex::new(grid));
+
+        let timer = Instant::now();
+        let a_star_len = AStarStrategy::new(grid.clone())
+            .path_finding(Some(start), Some(goal))
+            .map(|x| path_length(&x));
+        let a_star_ms = timer.elapsed().as_secs_f64() * 1000.0;
+
+        let mut hpa = HpaStrategy::new(grid.clone());
+        hpa.cluster_size = cluster_size;
+        let timer = Instant::now();
+        hpa.update_graph(&grid.lock().unwrap());
+        let build_ms = timer.elapsed().as_secs_f64() * 1000.0;
+
+        // Query the graph on the locked grid; `path_finding` would also time a copy of the grid.
+        let (query_ms, hpa_len) = {
+            let grid = grid.lock().unwrap();
+            let graph = hpa.graph.as_ref().unwrap();
+            let cells = (grid.get_from_world_pos(start).unwrap(), grid.get_from_world_pos(goal).unwrap());
+            let timer = Instant::now();
+            let found = graph.query(&grid, cells.0, cells.1);
+            let query_ms = timer.elapsed().as_secs_f64() * 1000.0;
+            let hpa_len = found.map(|(cells, _)| path_length(&world_path(&grid, start, goal, &cells)));
+            (query_ms, hpa_len)
+        };
+
+        let (x, y) = (size / 2, size / 2);
+        {
+            let mut grid = grid.lock().unwrap();
+            let flipped = if grid.is_wall(x, y) == Some(true) { GridCell::Air } else { GridCell::Wall };
+            grid.set(x, y, flipped);
+        }
+        let timer = Instant::now();
+        hpa.update_graph(&grid.lock().unwrap());
+        let rebuild_ms = timer.elapsed().as_secs_f64() * 1000.0;
+        let rebuilt_clusters = match hpa.last_update() {
+            Some(HpaUpdate::Rebuilt { clusters }) => clusters,
+            _ => 0,
+        };
+
+        Self {
+            size,
+            a_star_ms,
+            a_star_len,
+            build_ms,
+            query_ms,
+            hpa_len,
+            rebuild_ms,
+            rebuilt_clusters,
+        }
+    }
+
+    /// Random wall blocks, with the start and goal corners kept open.
+    fn generate(size: usize, rng: &mut StdRng) -> Grid {
+        let mut grid = Grid::new(size, size, 60.0, 20.0, Vec2::ZERO);
+        for _ in 0..size * size / 40 {
+            let (x, y) = (rng.random_range(0..size), rng.random_range(0..size));
+            let (w, h) = (rng.random_range(1..5), rng.random_range(1..5));
+            for cy in y..(y + h).min(size) {
+                for cx in x..(x + w).min(size) {
+                    grid.set(cx, cy, GridCell::Wall);
+                }
+            }
+        }
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (size - 1, size - 1), (size - 2, size - 1), (size - 1, size - 2)] {
+            grid.set(x, y, GridCell::Air);
+        }
+        grid
+    }
+}
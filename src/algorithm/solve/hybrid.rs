@@ -1,10 +1,22 @@
-use crate::algorithm::{grid::Grid, types::Ray};
+use crate::algorithm::{
+    clearance::{ClearanceCost, ClearanceMap},
+    distance_field::GeodesicField,
+    grid::Grid,
+    objective::{ObjectiveWeights, ParetoFront, ParetoTour, PathMetrics},
+};
 use bevy::prelude::*;
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fs,
     hash::Hash,
-    sync::{Arc, Mutex},
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 #[derive(Debug, Clone, Eq)]
@@ -49,6 +61,175 @@ impl PartialEq for Line {
     }
 }
 
+/// What the colony keeps of its global pheromone when the start or the goal moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PheromonePolicy {
+    /// Every trail stays as it is, still leading towards the old goal.
+    Keep,
+    /// Start over from `init_pheromone`.
+    Reset,
+    /// Pull every trail towards `init_pheromone`; 0 resets, 1 keeps.
+    Decay(f64),
+    /// Trails are laid without direction, so what was learnt about open corridors holds for any
+    /// start and goal, and they are kept as they are.
+    GoalIndependent,
+}
+
+impl PheromonePolicy {
+    pub fn next(&self) -> Self {
+        match self {
+            PheromonePolicy::Keep => PheromonePolicy::Reset,
+            PheromonePolicy::Reset => PheromonePolicy::Decay(0.5),
+            PheromonePolicy::Decay(_) => PheromonePolicy::GoalIndependent,
+            PheromonePolicy::GoalIndependent => PheromonePolicy::Keep,
+        }
+    }
+}
+
+/// On-disk form of the global pheromone, with the endpoints it was learnt for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PheromoneFile {
+    pixel_size: f32,
+    start: Option<(f32, f32)>,
+    goal: Option<(f32, f32)>,
+    goal_independent: bool,
+    trails: Vec<SavedTrail>,
+}
+
+/// One pheromone trail between two pixel nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedTrail {
+    from: (i32, i32),
+    to: (i32, i32),
+    value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AntStatus {
+    Arrived,
+    Stuck,
+    Dead,
+}
+
+#[derive(Debug, Clone)]
+pub struct AntTour {
+    pub path: Vec<Vec2>,
+    pub alpha: f64,
+    pub beta: f64,
+    pub status: AntStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColonyIteration {
+    pub index: u64,
+    pub tours: Vec<AntTour>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridParam {
+    ExploitationChance,
+    ElicitationConstant,
+    EvaporationCoefficient,
+    DepositConstant,
+    GlobalEvaporationCoefficient,
+    GlobalDepositConstant,
+    InitPheromone,
+    AntNumber,
+    MaxAntTry,
+    ParticleInertia,
+    ParticleGlobalFactor,
+    ParticleLocalFactor,
+    InitAlphaMin,
+    InitAlphaMax,
+    InitBetaMin,
+    InitBetaMax,
+}
+
+impl HybridParam {
+    pub fn iter() -> std::slice::Iter<'static, Self> {
+        static PARAMS: [HybridParam; 16] = [
+            HybridParam::ExploitationChance,
+            HybridParam::ElicitationConstant,
+            HybridParam::EvaporationCoefficient,
+            HybridParam::DepositConstant,
+            HybridParam::GlobalEvaporationCoefficient,
+            HybridParam::GlobalDepositConstant,
+            HybridParam::InitPheromone,
+            HybridParam::AntNumber,
+            HybridParam::MaxAntTry,
+            HybridParam::ParticleInertia,
+            HybridParam::ParticleGlobalFactor,
+            HybridParam::ParticleLocalFactor,
+            HybridParam::InitAlphaMin,
+            HybridParam::InitAlphaMax,
+            HybridParam::InitBetaMin,
+            HybridParam::InitBetaMax,
+        ];
+        PARAMS.iter()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HybridParam::ExploitationChance => "exploitation_chance",
+            HybridParam::ElicitationConstant => "elicitation_constant",
+            HybridParam::EvaporationCoefficient => "evaporation_coefficient",
+            HybridParam::DepositConstant => "deposit_constant",
+            HybridParam::GlobalEvaporationCoefficient => "global_evaporation_coefficient",
+            HybridParam::GlobalDepositConstant => "global_deposit_constant",
+            HybridParam::InitPheromone => "init_pheromone",
+            HybridParam::AntNumber => "ant_number",
+            HybridParam::MaxAntTry => "max_ant_try",
+            HybridParam::ParticleInertia => "particle_inertia",
+            HybridParam::ParticleGlobalFactor => "particle_global_factor",
+            HybridParam::ParticleLocalFactor => "particle_local_factor",
+            HybridParam::InitAlphaMin => "init_alpha_min",
+            HybridParam::InitAlphaMax => "init_alpha_max",
+            HybridParam::InitBetaMin => "init_beta_min",
+            HybridParam::InitBetaMax => "init_beta_max",
+        }
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            HybridParam::ExploitationChance
+            | HybridParam::EvaporationCoefficient
+            | HybridParam::GlobalEvaporationCoefficient => (0.0, 1.0),
+            HybridParam::ElicitationConstant => (0.0, 10000.0),
+            HybridParam::DepositConstant | HybridParam::GlobalDepositConstant => (0.0, 20000.0),
+            HybridParam::InitPheromone => (0.01, 10.0),
+            HybridParam::AntNumber => (1.0, 100.0),
+            HybridParam::MaxAntTry => (1.0, 5000.0),
+            HybridParam::ParticleInertia => (0.0, 1.5),
+            HybridParam::ParticleGlobalFactor | HybridParam::ParticleLocalFactor => (0.0, 4.0),
+            HybridParam::InitAlphaMin
+            | HybridParam::InitAlphaMax
+            | HybridParam::InitBetaMin
+            | HybridParam::InitBetaMax => (0.1, 5.0),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        HybridParam::iter().find(|x| x.name() == name).copied()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, HybridParam::AntNumber | HybridParam::MaxAntTry)
+    }
+
+    /// Parameters that the particles are built from; changing them needs a fresh swarm.
+    pub fn invalidates_state(&self) -> bool {
+        matches!(
+            self,
+            HybridParam::AntNumber
+                | HybridParam::InitAlphaMin
+                | HybridParam::InitAlphaMax
+                | HybridParam::InitBetaMin
+                | HybridParam::InitBetaMax
+        )
+    }
+}
+
+#[derive(Clone)]
 pub struct HybridStrategy {
     pub exploitation_chance: f64,
     pub elicitation_constant: f64,
@@ -69,16 +250,38 @@ pub struct HybridStrategy {
     pub init_beta_min: f64,
     pub init_beta_max: f64,
 
+    pub tour_history_size: usize,
+
+    pub agent_radius: f32,
+    pub clearance_cost: ClearanceCost,
+    pub geodesic_heuristic: bool,
+    pub objective: ObjectiveWeights,
+    /// Keep every non-dominated tour and return the one at `pareto_choice` instead of the cheapest.
+    pub pareto: bool,
+    pub pareto_choice: usize,
+    /// After wall edits, reroute the blocked steps of the best tour instead of forgetting it.
+    pub warm_start: bool,
+    pheromone_policy: PheromonePolicy,
+
     grid: Arc<Mutex<Grid>>,
+    /// Built at the grid revision it is tagged with; reused until the walls change.
+    clearance_map: Option<(u64, ClearanceMap)>,
+    geodesic_field: Option<Arc<GeodesicField>>,
     global_pheromones: HashMap<Line, f64>,
     global_best_path: Option<Vec<Node>>,
     global_best_len: f64,
+    pareto_front: ParetoFront,
     cache_start: Option<Vec2>,
     cache_goal: Option<Vec2>,
     particles: Vec<(f64, f64)>,
     local_particle_best: Vec<(f64, f64)>,
     particle_best_len: Vec<f64>,
     global_particle_best: Option<(f64, f64)>,
+    seed: Option<u64>,
+    rng: StdRng,
+    iteration: u64,
+    tour_history: VecDeque<ColonyIteration>,
+    cancel_flag: Option<Arc<AtomicBool>>,
 }
 
 impl HybridStrategy {
@@ -103,16 +306,35 @@ impl HybridStrategy {
             init_beta_min: 0.5,
             init_beta_max: 3.0,
 
+            tour_history_size: 50,
+
+            agent_radius: 0.0,
+            clearance_cost: ClearanceCost::default(),
+            geodesic_heuristic: false,
+            objective: ObjectiveWeights::default(),
+            pareto: false,
+            pareto_choice: 0,
+            warm_start: false,
+            pheromone_policy: PheromonePolicy::Keep,
+
             grid,
+            clearance_map: None,
+            geodesic_field: None,
             global_pheromones: HashMap::new(),
             global_best_path: None,
             global_best_len: f64::INFINITY,
+            pareto_front: ParetoFront::new(16),
             cache_start: None,
             cache_goal: None,
             particles: Vec::new(),
             local_particle_best: Vec::new(),
             particle_best_len: Vec::new(),
             global_particle_best: None,
+            seed: None,
+            rng: StdRng::from_os_rng(),
+            iteration: 0,
+            tour_history: VecDeque::new(),
+            cancel_flag: None,
         }
     }
 }
@@ -122,15 +344,161 @@ impl HybridStrategy {
         self.global_pheromones.clear();
         self.global_best_path = None;
         self.global_best_len = f64::INFINITY;
+        self.pareto_front.clear();
+        self.iteration = 0;
+        self.tour_history.clear();
+        if let Some(seed) = self.seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn pheromone_policy(&self) -> PheromonePolicy {
+        self.pheromone_policy
+    }
+
+    /// Switching to or from the goal-independent policy rewrites the trails under the new key
+    /// convention, so nothing learnt so far is left where it is never looked up.
+    pub fn set_pheromone_policy(&mut self, policy: PheromonePolicy) {
+        let was_undirected = self.pheromone_policy == PheromonePolicy::GoalIndependent;
+        self.pheromone_policy = policy;
+        let pheromones = std::mem::take(&mut self.global_pheromones);
+        self.global_pheromones = self.convert_trails(pheromones, was_undirected);
+    }
+
+    /// Reseeds the random source; `None` falls back to OS entropy.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+    }
+
+    pub fn reset_particles(&mut self) {
+        self.particles.clear();
+        self.local_particle_best.clear();
+        self.particle_best_len.clear();
+        self.global_particle_best = None;
+    }
+
+    pub fn get_param(&self, param: HybridParam) -> f64 {
+        match param {
+            HybridParam::ExploitationChance => self.exploitation_chance,
+            HybridParam::ElicitationConstant => self.elicitation_constant,
+            HybridParam::EvaporationCoefficient => self.evaporation_coefficient,
+            HybridParam::DepositConstant => self.deposit_constant,
+            HybridParam::GlobalEvaporationCoefficient => self.global_evaporation_coefficient,
+            HybridParam::GlobalDepositConstant => self.global_deposit_constant,
+            HybridParam::InitPheromone => self.init_pheromone,
+            HybridParam::AntNumber => self.ant_number as f64,
+            HybridParam::MaxAntTry => self.max_ant_try as f64,
+            HybridParam::ParticleInertia => self.particle_inertia,
+            HybridParam::ParticleGlobalFactor => self.particle_global_factor,
+            HybridParam::ParticleLocalFactor => self.particle_local_factor,
+            HybridParam::InitAlphaMin => self.init_alpha_min,
+            HybridParam::InitAlphaMax => self.init_alpha_max,
+            HybridParam::InitBetaMin => self.init_beta_min,
+            HybridParam::InitBetaMax => self.init_beta_max,
+        }
+    }
+
+    /// Clamps `value` into the parameter's valid range and applies it, resetting the
+    /// colony when the change invalidates it. Returns the value actually applied.
+    pub fn set_param(&mut self, param: HybridParam, value: f64) -> f64 {
+        let (min, max) = param.range();
+        // Sampling needs a non-empty range, so each bound stays clear of its partner.
+        let (min, max) = match param {
+            HybridParam::InitAlphaMin => (min, self.init_alpha_max - 0.01),
+            HybridParam::InitAlphaMax => (self.init_alpha_min + 0.01, max),
+            HybridParam::InitBetaMin => (min, self.init_beta_max - 0.01),
+            HybridParam::InitBetaMax => (self.init_beta_min + 0.01, max),
+            _ => (min, max),
+        };
+        let mut value = value.clamp(min, max);
+        if param.is_integer() {
+            value = value.round();
+        }
+
+        if value == self.get_param(param) {
+            return value;
+        }
+
+        match param {
+            HybridParam::ExploitationChance => self.exploitation_chance = value,
+            HybridParam::ElicitationConstant => self.elicitation_constant = value,
+            HybridParam::EvaporationCoefficient => self.evaporation_coefficient = value,
+            HybridParam::DepositConstant => self.deposit_constant = value,
+            HybridParam::GlobalEvaporationCoefficient => {
+                self.global_evaporation_coefficient = value
+            }
+            HybridParam::GlobalDepositConstant => self.global_deposit_constant = value,
+            HybridParam::InitPheromone => self.init_pheromone = value,
+            HybridParam::AntNumber => self.ant_number = value as u32,
+            HybridParam::MaxAntTry => self.max_ant_try = value as u32,
+            HybridParam::ParticleInertia => self.particle_inertia = value,
+            HybridParam::ParticleGlobalFactor => self.particle_global_factor = value,
+            HybridParam::ParticleLocalFactor => self.particle_local_factor = value,
+            HybridParam::InitAlphaMin => self.init_alpha_min = value,
+            HybridParam::InitAlphaMax => self.init_alpha_max = value,
+            HybridParam::InitBetaMin => self.init_beta_min = value,
+            HybridParam::InitBetaMax => self.init_beta_max = value,
+        }
+
+        if param.invalidates_state() {
+            self.reset();
+            self.reset_particles();
+        }
+
+        value
+    }
+
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
+
+    /// Once the flag is raised, a running `path_finding` gives up and returns `None`.
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    pub fn tour_history(&self) -> &VecDeque<ColonyIteration> {
+        &self.tour_history
+    }
+
+    /// Weighted cost of the best tour; its length while `objective` only weights length.
+    pub fn global_best_len(&self) -> f64 {
+        self.global_best_len
+    }
+
+    pub fn pareto_front(&self) -> &ParetoFront {
+        &self.pareto_front
+    }
+
+    pub fn pheromone_edges(&self) -> Vec<(Vec2, Vec2, f64)> {
+        self.global_pheromones
+            .iter()
+            .map(|(line, pheromone)| {
+                (
+                    self.node_to_world_pos(line.from.clone()),
+                    self.node_to_world_pos(line.to.clone()),
+                    *pheromone,
+                )
+            })
+            .collect()
     }
 
     pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
         let start = start?;
         let goal = goal?;
 
-        if self.particles.is_empty() {
+        if self.particles.len() != self.ant_number as usize {
+            self.reset_particles();
             for _ in 0..self.ant_number {
-                let mut rng = rand::rng();
+                let rng = &mut self.rng;
                 let alpha = rng.random_range(self.init_alpha_min..self.init_alpha_max);
                 let beta = rng.random_range(self.init_beta_min..self.init_beta_max);
                 self.particles.push((alpha, beta));
@@ -140,6 +508,10 @@ impl HybridStrategy {
             }
         }
 
+        if self.cache_start != Some(start) || self.cache_goal != Some(goal) {
+            self.pareto_front.clear();
+            self.apply_pheromone_policy();
+        }
         if let Some(cached) = self.cache_start {
             if cached != start {
                 self.global_best_len = f64::INFINITY;
@@ -170,10 +542,36 @@ impl HybridStrategy {
                 }
             }
             if path_blocked {
-                self.global_best_len = f64::INFINITY;
-                self.global_best_path = None;
+                let rerouted = if self.warm_start { self.reroute(path) } else { None };
+                match rerouted {
+                    Some(path) => {
+                        self.global_best_len = self.tour_cost(&path);
+                        self.global_best_path = Some(path);
+                    }
+                    None => {
+                        self.global_best_len = f64::INFINITY;
+                        self.global_best_path = None;
+                    }
+                }
+            }
+        }
+        let mut front = self.pareto_front.clone();
+        front.retain(|x| x.path.windows(2).all(|w| self.has_sight(w[0], w[1])));
+        self.pareto_front = front;
+
+        if !self.clearance_cost.is_enabled() {
+            self.clearance_map = None;
+        } else {
+            let grid = self.grid.lock().unwrap();
+            if self.clearance_map.as_ref().is_none_or(|x| x.0 != grid.revision()) {
+                self.clearance_map = Some((grid.revision(), ClearanceMap::from_grid(&grid)));
             }
         }
+        self.geodesic_field = if self.geodesic_heuristic {
+            self.grid.lock().unwrap().geodesic_field(goal)
+        } else {
+            None
+        };
 
         let start_node = self.world_to_node_pos(start).unwrap();
         let goal_node = self.world_to_node_pos(goal).unwrap();
@@ -196,7 +594,18 @@ impl HybridStrategy {
         let mut ants_path_len: Vec<f64> = Vec::new();
         ants_path_len.resize(self.ant_number as usize, 0.0);
 
+        let record_tours = self.tour_history_size > 0;
+        let mut ants_death_step: Vec<Option<usize>> = vec![None; self.ant_number as usize];
+
         for _ in 0..self.max_ant_try {
+            if self
+                .cancel_flag
+                .as_ref()
+                .is_some_and(|x| x.load(Ordering::Relaxed))
+            {
+                return None;
+            }
+
             for ant_idx in 0..self.ant_number {
                 let cur_ant_node = ants_cur_path.get(ant_idx as usize).unwrap().last().unwrap();
                 if *cur_ant_node == goal_node {
@@ -210,12 +619,18 @@ impl HybridStrategy {
                     &pheromones,
                     &cur_tabu,
                     goal,
-                    1.2,
-                    1.8,
-                    // self.particles[ant_idx as usize].0,
-                    // self.particles[ant_idx as usize].1,
+                    self.particles[ant_idx as usize].0,
+                    self.particles[ant_idx as usize].1,
                 );
-                let cur_line = Line::new(cur_ant_node.clone(), next_ant_node.clone());
+                let cur_line = self.trail(cur_ant_node.clone(), next_ant_node.clone());
+
+                let death_step = &mut ants_death_step[ant_idx as usize];
+                if record_tours
+                    && death_step.is_none()
+                    && !self.node_has_sight(cur_ant_node.clone(), next_ant_node.clone())
+                {
+                    *death_step = Some(ants_cur_path[ant_idx as usize].len());
+                }
 
                 let cur_path_len = ants_path_len.get_mut(ant_idx as usize).unwrap();
                 *cur_path_len += Vec2::distance(
@@ -244,41 +659,32 @@ impl HybridStrategy {
             }
         }
 
+        if record_tours {
+            self.record_iteration(&ants_cur_path, &ants_death_step, &goal_node);
+        }
+        self.iteration += 1;
+
+        let ants_cost = self.score_tours(&ants_cur_path, &ants_path_len, &goal_node);
+
         let mut best_path = None;
         let mut best_path_len = f64::INFINITY;
         for ant_idx in 0..self.ant_number {
             let path = &ants_cur_path[ant_idx as usize];
-            if path.last() == Some(&goal_node) && ants_path_len[ant_idx as usize] < best_path_len {
+            if path.last() == Some(&goal_node) && ants_cost[ant_idx as usize] < best_path_len {
                 best_path = Some(path);
-                best_path_len = ants_path_len[ant_idx as usize];
+                best_path_len = ants_cost[ant_idx as usize];
             }
 
             if path.last() == Some(&goal_node)
-                && ants_path_len[ant_idx as usize] < self.particle_best_len[ant_idx as usize]
+                && ants_cost[ant_idx as usize] < self.particle_best_len[ant_idx as usize]
             {
-                self.particle_best_len[ant_idx as usize] = ants_path_len[ant_idx as usize];
+                self.particle_best_len[ant_idx as usize] = ants_cost[ant_idx as usize];
                 self.local_particle_best[ant_idx as usize] = self.particles[ant_idx as usize];
             }
         }
 
         if let Some(path) = best_path {
-            path.windows(2).for_each(|x| {
-                if let Some(p) = self
-                    .global_pheromones
-                    .get_mut(&Line::new(x[0].clone(), x[1].clone()))
-                {
-                    *p = (1.0 - self.global_evaporation_coefficient) * *p
-                        + self.global_evaporation_coefficient
-                            * ((self.global_deposit_constant + 1.0) / (best_path_len + 1.0));
-                } else {
-                    self.global_pheromones.insert(
-                        Line::new(x[0].clone(), x[1].clone()),
-                        (1.0 - self.global_evaporation_coefficient) * self.init_pheromone
-                            + self.global_evaporation_coefficient
-                                * ((self.global_deposit_constant + 1.0) / (best_path_len + 1.0)),
-                    );
-                }
-            });
+            self.deposit_global(path, best_path_len);
 
             if best_path_len < self.global_best_len {
                 self.global_best_path = Some(path.clone());
@@ -297,7 +703,7 @@ impl HybridStrategy {
             let (local_best_alpha, local_best_beta) = self.local_particle_best[ant_idx as usize];
             let (global_best_alpha, global_best_beta) = self.global_particle_best.unwrap();
 
-            let mut rng = rand::rng();
+            let rng = &mut self.rng;
             let new_alpha = self.particle_inertia * cur_alpha
                 + self.particle_local_factor
                     * rng.random_range(0.0..1.0)
@@ -313,7 +719,24 @@ impl HybridStrategy {
                     * rng.random_range(0.0..1.0)
                     * (global_best_beta - cur_beta);
 
-            self.particles[ant_idx as usize] = (new_alpha, new_beta);
+            // Keep the exponents inside their slider ranges so the move weights stay finite.
+            let (alpha_min, alpha_max) = HybridParam::InitAlphaMin.range();
+            let (beta_min, beta_max) = HybridParam::InitBetaMin.range();
+            self.particles[ant_idx as usize] =
+                (new_alpha.clamp(alpha_min, alpha_max), new_beta.clamp(beta_min, beta_max));
+        }
+
+        self.best_path()
+    }
+
+    /// The tour `path_finding` hands out: the Pareto tour at `pareto_choice` in Pareto mode once
+    /// there is one, the cheapest tour otherwise.
+    pub fn best_path(&self) -> Option<Vec<Vec2>> {
+        if self.pareto {
+            let tours = self.pareto_front.tours();
+            if let Some(tour) = tours.get(self.pareto_choice.min(tours.len().saturating_sub(1))) {
+                return Some(tour.path.clone());
+            }
         }
 
         self.global_best_path.clone().map(|path| {
@@ -323,8 +746,315 @@ impl HybridStrategy {
         })
     }
 
+    /// Lays a global pheromone trail along a path found elsewhere (A*, another solver, an earlier
+    /// run) so the colony starts out around it. The path also becomes the best tour when it is
+    /// clear and beats the current one. Returns whether it did.
+    pub fn seed_pheromones(&mut self, path: &[Vec2]) -> bool {
+        let (Some(start), Some(goal)) = (path.first().copied(), path.last().copied()) else {
+            return false;
+        };
+        let Some(nodes) = self.rasterize(path) else {
+            return false;
+        };
+        // Rounding onto the lattice can clip a corner the original path only grazed.
+        let nodes = self.reroute(&nodes).unwrap_or(nodes);
+
+        let cost = self.tour_cost(&nodes);
+        self.deposit_global(&nodes, cost);
+
+        // A seed for other endpoints would be thrown away on the next plan anyway.
+        if self.cache_start != Some(start) || self.cache_goal != Some(goal) {
+            self.global_best_path = None;
+            self.global_best_len = f64::INFINITY;
+            self.pareto_front.clear();
+            self.cache_start = Some(start);
+            self.cache_goal = Some(goal);
+        }
+        let clear = nodes
+            .windows(2)
+            .all(|x| self.node_has_sight(x[0].clone(), x[1].clone()));
+        if clear && cost < self.global_best_len {
+            self.global_best_path = Some(nodes);
+            self.global_best_len = cost;
+            return true;
+        }
+        false
+    }
+
+    /// Writes the global pheromone to a RON file. Returns how many trails were saved.
+    pub fn save_pheromones(&self, path: impl AsRef<Path>) -> Result<usize, String> {
+        let path = path.as_ref();
+        let mut trails: Vec<_> = self
+            .global_pheromones
+            .iter()
+            .map(|(line, value)| SavedTrail {
+                from: line.from.pos,
+                to: line.to.pos,
+                value: *value,
+            })
+            .collect();
+        trails.sort_by_key(|x| (x.from, x.to));
+        let file = PheromoneFile {
+            pixel_size: self.grid.lock().unwrap().pixel_size(),
+            start: self.cache_start.map(|x| (x.x, x.y)),
+            goal: self.cache_goal.map(|x| (x.x, x.y)),
+            goal_independent: self.pheromone_policy == PheromonePolicy::GoalIndependent,
+            trails,
+        };
+        let text = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        Ok(file.trails.len())
+    }
+
+    /// Replaces the global pheromone with a saved one and forgets the best tour. The saved
+    /// endpoints become the cached ones, so `pheromone_policy` decides what carries over to a
+    /// query elsewhere. Returns how many trails were loaded.
+    pub fn load_pheromones(&mut self, path: impl AsRef<Path>) -> Result<usize, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let file: PheromoneFile = ron::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let pixel_size = self.grid.lock().unwrap().pixel_size();
+        if file.pixel_size != pixel_size {
+            return Err(format!(
+                "{}: saved for pixel size {}, the grid uses {}",
+                path.display(),
+                file.pixel_size,
+                pixel_size
+            ));
+        }
+
+        let pheromones = file
+            .trails
+            .iter()
+            .map(|x| (Line::new(Node::new(x.from.0, x.from.1), Node::new(x.to.0, x.to.1)), x.value))
+            .collect();
+        self.global_pheromones = self.convert_trails(pheromones, file.goal_independent);
+        self.global_best_path = None;
+        self.global_best_len = f64::INFINITY;
+        self.pareto_front.clear();
+        self.cache_start = file.start.map(|(x, y)| Vec2::new(x, y));
+        self.cache_goal = file.goal.map(|(x, y)| Vec2::new(x, y));
+        Ok(self.global_pheromones.len())
+    }
+
+    fn apply_pheromone_policy(&mut self) {
+        match self.pheromone_policy {
+            PheromonePolicy::Keep | PheromonePolicy::GoalIndependent => {}
+            PheromonePolicy::Reset => self.global_pheromones.clear(),
+            PheromonePolicy::Decay(factor) => {
+                let init = self.init_pheromone;
+                for value in self.global_pheromones.values_mut() {
+                    *value = init + (*value - init) * factor;
+                }
+            }
+        }
+    }
+
+    /// Rekeys trails stored with or without direction for the current policy. Undirected trails
+    /// are laid both ways; directed ones fold into one undirected trail, the stronger way winning.
+    fn convert_trails(&self, pheromones: HashMap<Line, f64>, undirected: bool) -> HashMap<Line, f64> {
+        if undirected == (self.pheromone_policy == PheromonePolicy::GoalIndependent) {
+            return pheromones;
+        }
+
+        let mut result = HashMap::new();
+        for (line, value) in pheromones {
+            if undirected {
+                result.insert(Line::new(line.to.clone(), line.from.clone()), value);
+                result.insert(line, value);
+            } else {
+                let trail = self.trail(line.from, line.to);
+                let entry = result.entry(trail).or_insert(value);
+                *entry = entry.max(value);
+            }
+        }
+        result
+    }
+
+    /// Key of the trail from one node to the next; undirected under the goal-independent policy.
+    fn trail(&self, from: Node, to: Node) -> Line {
+        if self.pheromone_policy == PheromonePolicy::GoalIndependent && to.pos < from.pos {
+            Line::new(to, from)
+        } else {
+            Line::new(from, to)
+        }
+    }
+
+    fn deposit_global(&mut self, path: &[Node], cost: f64) {
+        for x in path.windows(2) {
+            let line = self.trail(x[0].clone(), x[1].clone());
+            let previous = self.global_pheromones.get(&line).copied().unwrap_or(self.init_pheromone);
+            self.global_pheromones.insert(
+                line,
+                (1.0 - self.global_evaporation_coefficient) * previous
+                    + self.global_evaporation_coefficient
+                        * ((self.global_deposit_constant + 1.0) / (cost + 1.0)),
+            );
+        }
+    }
+
+    /// Cost of one node path, measured the way `score_tours` measures the ants.
+    fn tour_cost(&self, path: &[Node]) -> f64 {
+        let path: Vec<Vec2> = path.iter().map(|x| self.node_to_world_pos(x.clone())).collect();
+        if self.objective.is_length_only() && !self.pareto {
+            return path.windows(2).map(|x| x[0].distance(x[1]) as f64).sum();
+        }
+        let metrics = PathMetrics::measure(&self.grid.lock().unwrap(), &path);
+        self.objective.cost(&metrics)
+    }
+
+    /// Node path through every point of a world path, one 8-connected step at a time.
+    fn rasterize(&self, path: &[Vec2]) -> Option<Vec<Node>> {
+        let mut nodes = vec![self.world_to_node_pos(path[0])?];
+        for point in path[1..].iter() {
+            let from = nodes[nodes.len() - 1].pos;
+            let to = self.world_to_node_pos(*point)?.pos;
+            let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs());
+            for i in 1..=steps {
+                let t = i as f32 / steps as f32;
+                nodes.push(Node::new(
+                    from.0 + ((to.0 - from.0) as f32 * t).round() as i32,
+                    from.1 + ((to.1 - from.1) as f32 * t).round() as i32,
+                ));
+            }
+        }
+        Some(nodes)
+    }
+
+    /// Replaces each blocked step of `path` by the shortest detour to a later node of the same
+    /// path, searched over a bounded number of nodes. `None` when some step cannot be rerouted.
+    fn reroute(&self, path: &[Node]) -> Option<Vec<Node>> {
+        let mut rerouted = vec![path[0].clone()];
+        let mut i = 0;
+        while i + 1 < path.len() {
+            if self.node_has_sight(path[i].clone(), path[i + 1].clone()) {
+                rerouted.push(path[i + 1].clone());
+                i += 1;
+                continue;
+            }
+            let (detour, rejoin) = self.detour(&path[i], &path[i + 1..])?;
+            rerouted.extend(detour.into_iter().skip(1));
+            i = i + 1 + rejoin;
+        }
+        Some(rerouted)
+    }
+
+    /// Dijkstra over the node lattice from `from` until it reaches any of `targets`; returns the
+    /// detour and the index of the target it rejoined at.
+    fn detour(&self, from: &Node, targets: &[Node]) -> Option<(Vec<Node>, usize)> {
+        const MAX_EXPANSIONS: usize = 4000;
+
+        let target_index: HashMap<&Node, usize> =
+            targets.iter().enumerate().rev().map(|(i, x)| (x, i)).collect();
+        let mut cost: HashMap<Node, f64> = HashMap::from([(from.clone(), 0.0)]);
+        let mut parent: HashMap<Node, Node> = HashMap::new();
+        let mut open = BinaryHeap::from([(Reverse(0u64), from.pos)]);
+
+        for _ in 0..MAX_EXPANSIONS {
+            let (Reverse(key), pos) = open.pop()?;
+            let node = Node { pos };
+            if ((cost[&node] * 1000.0) as u64) < key {
+                continue;
+            }
+            // Any later node will do, so the detour never doubles back along the old path.
+            if let Some(&index) = target_index.get(&node).filter(|_| node != *from) {
+                let mut detour = vec![node.clone()];
+                while let Some(previous) = parent.get(&detour[detour.len() - 1]) {
+                    detour.push(previous.clone());
+                }
+                detour.reverse();
+                return Some((detour, index));
+            }
+
+            let node_cost = cost[&node];
+            for next in self.next_node_list(node.clone()) {
+                if !self.node_has_sight(node.clone(), next.clone()) {
+                    continue;
+                }
+                let step = (next.pos.0 - pos.0).abs() + (next.pos.1 - pos.1).abs();
+                let next_cost = node_cost + if step == 2 { std::f64::consts::SQRT_2 } else { 1.0 };
+                if cost.get(&next).is_none_or(|x| next_cost < *x) {
+                    cost.insert(next.clone(), next_cost);
+                    parent.insert(next.clone(), node.clone());
+                    // Costs are in lattice steps; the scaled integer key keeps the heap ordered.
+                    open.push((Reverse((next_cost * 1000.0) as u64), next.pos));
+                }
+            }
+        }
+        None
+    }
+
+    /// Cost of every ant's tour under `objective`, infinite for ants that missed the goal.
+    /// In Pareto mode the arrived tours are also offered to the front.
+    fn score_tours(&mut self, ants_cur_path: &[Vec<Node>], ants_path_len: &[f64], goal_node: &Node) -> Vec<f64> {
+        if self.objective.is_length_only() && !self.pareto {
+            return ants_path_len.to_vec();
+        }
+
+        let paths: Vec<Option<Vec<Vec2>>> = ants_cur_path
+            .iter()
+            .map(|path| {
+                (path.last() == Some(goal_node))
+                    .then(|| path.iter().map(|x| self.node_to_world_pos(x.clone())).collect())
+            })
+            .collect();
+
+        let grid = self.grid.lock().unwrap();
+        let mut costs = Vec::with_capacity(paths.len());
+        for path in paths {
+            let Some(path) = path else {
+                costs.push(f64::INFINITY);
+                continue;
+            };
+            let metrics = PathMetrics::measure(&grid, &path);
+            let cost = self.objective.cost(&metrics);
+            if self.pareto {
+                self.pareto_front.insert(ParetoTour { path, metrics, cost });
+            }
+            costs.push(cost);
+        }
+        costs
+    }
+
+    fn record_iteration(
+        &mut self,
+        ants_cur_path: &[Vec<Node>],
+        ants_death_step: &[Option<usize>],
+        goal_node: &Node,
+    ) {
+        let tours = ants_cur_path
+            .iter()
+            .zip(ants_death_step)
+            .enumerate()
+            .map(|(ant_idx, (path, death_step))| {
+                let (alpha, beta) = self.particles[ant_idx];
+                let (status, path) = match death_step {
+                    Some(step) => (AntStatus::Dead, &path[..=*step]),
+                    None if path.last() == Some(goal_node) => (AntStatus::Arrived, &path[..]),
+                    None => (AntStatus::Stuck, &path[..]),
+                };
+
+                AntTour {
+                    path: path.iter().map(|x| self.node_to_world_pos(x.clone())).collect(),
+                    alpha,
+                    beta,
+                    status,
+                }
+            })
+            .collect();
+
+        while self.tour_history.len() >= self.tour_history_size {
+            self.tour_history.pop_front();
+        }
+        self.tour_history.push_back(ColonyIteration {
+            index: self.iteration,
+            tours,
+        });
+    }
+
     fn calculate_next_node(
-        &self,
+        &mut self,
         node: Node,
         pheromones: &HashMap<Line, f64>,
         tabu: &HashSet<Node>,
@@ -332,7 +1062,7 @@ impl HybridStrategy {
         alpha: f64,
         beta: f64,
     ) -> Node {
-        let is_exploit = HybridStrategy::roll(vec![
+        let is_exploit = HybridStrategy::roll(&mut self.rng, vec![
             self.exploitation_chance,
             1.0 - self.exploitation_chance,
         ]);
@@ -360,7 +1090,7 @@ impl HybridStrategy {
                 }
             }
         } else {
-            res = next_nodes[HybridStrategy::roll(next_values)].clone();
+            res = next_nodes[HybridStrategy::roll(&mut self.rng, next_values)].clone();
         }
 
         res
@@ -383,12 +1113,27 @@ impl HybridStrategy {
             return 0.0000000001;
         }
 
-        (*pheromones.get(&line).unwrap_or(&self.init_pheromone)).powf(alpha)
+        let trail = self.trail(line.from.clone(), line.to.clone());
+        (*pheromones.get(&trail).unwrap_or(&self.init_pheromone)).powf(alpha)
             * self.get_heuristic(line.to.clone(), goal).powf(beta)
     }
     fn get_heuristic(&self, node: Node, goal: Vec2) -> f64 {
         let wpos = self.node_to_world_pos(node);
-        (self.elicitation_constant + 1.0) / (Vec2::distance(wpos, goal) as f64 + 1.0)
+        // Cells walled off from the goal keep the straight-line value so every move has some weight.
+        let distance = self
+            .geodesic_field
+            .as_ref()
+            .and_then(|x| x.distance_at(wpos))
+            .unwrap_or_else(|| Vec2::distance(wpos, goal));
+        let heuristic = (self.elicitation_constant + 1.0) / (distance as f64 + 1.0);
+
+        match self.clearance_map.as_ref() {
+            Some((_, map)) => {
+                let penalty = self.clearance_cost.penalty(map.at(wpos) - self.agent_radius);
+                heuristic / (1.0 + penalty as f64)
+            }
+            None => heuristic,
+        }
     }
 }
 
@@ -429,29 +1174,17 @@ impl HybridStrategy {
         self.has_sight(self.node_to_world_pos(nfrom), self.node_to_world_pos(nto))
     }
 
+    /// Line of sight for a circle of `agent_radius`; a plain ray when the radius is zero.
     fn has_sight(&self, from: Vec2, to: Vec2) -> bool {
-        let direction = (to - from).normalize_or_zero();
-        let distance = from.distance(to);
-        if distance == 0.0 {
-            return true;
-        }
-
-        let ray = Ray {
-            root: from,
-            dir: direction,
-        };
-
         self.grid
             .lock()
             .unwrap()
-            .raycast(ray)
-            .map_or(true, |hit| hit.dist >= distance)
+            .is_segment_clear(from, to, self.agent_radius)
     }
 
-    fn roll(weights: Vec<f64>) -> usize {
+    fn roll(rng: &mut StdRng, weights: Vec<f64>) -> usize {
         let total: f64 = weights.iter().sum();
 
-        let mut rng = rand::rng();
         let mut random = rng.random_range(0.0..total);
 
         for (i, &weight) in weights.iter().enumerate() {
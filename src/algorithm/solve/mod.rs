@@ -1,4 +1,11 @@
 pub mod a_star;
+pub mod anytime;
+pub mod d_star_lite;
+pub mod genetic;
+pub mod hpa;
+pub mod prm;
+pub mod rrt;
+pub mod visibility;
 
 pub mod hybrid;
 mod pso;
@@ -0,0 +1,440 @@
+use crate::algorithm::grid::{CellChange, Grid};
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
+};
+
+/// How the last query got its roadmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoadmapUpdate {
+    Built,
+    Repaired { removed: usize, added: usize },
+    Reused,
+}
+
+#[derive(Debug, Clone)]
+struct Roadmap {
+    nodes: Vec<Vec2>,
+    alive: Vec<bool>,
+    edges: Vec<Vec<(usize, f32)>>,
+    revision: u64,
+    agent_radius: f32,
+    samples: usize,
+}
+
+impl Roadmap {
+    fn add_node(&mut self, pos: Vec2) -> usize {
+        self.nodes.push(pos);
+        self.alive.push(true);
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    fn is_linked(&self, a: usize, b: usize) -> bool {
+        self.edges[a].iter().any(|x| x.0 == b)
+    }
+
+    fn link(&mut self, a: usize, b: usize) {
+        let cost = self.nodes[a].distance(self.nodes[b]);
+        self.edges[a].push((b, cost));
+        self.edges[b].push((a, cost));
+    }
+
+    /// Alive nodes closest to `pos` first.
+    fn by_distance(&self, pos: Vec2) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.nodes.len()).filter(|&i| self.alive[i]).collect();
+        order.sort_by(|&a, &b| {
+            self.nodes[a]
+                .distance_squared(pos)
+                .total_cmp(&self.nodes[b].distance_squared(pos))
+        });
+        order
+    }
+
+    /// Drops dead nodes and renumbers the edges of the rest.
+    fn compact(&mut self) {
+        let mut index = vec![usize::MAX; self.nodes.len()];
+        let mut kept = 0;
+        for (i, slot) in index.iter_mut().enumerate() {
+            if self.alive[i] {
+                *slot = kept;
+                kept += 1;
+            }
+        }
+        let alive = std::mem::take(&mut self.alive);
+        let nodes = std::mem::take(&mut self.nodes);
+        let edges = std::mem::take(&mut self.edges);
+        for ((node, edges), _) in nodes.into_iter().zip(edges).zip(alive).filter(|x| x.1) {
+            self.nodes.push(node);
+            self.alive.push(true);
+            self.edges.push(edges.into_iter().map(|(j, cost)| (index[j], cost)).collect());
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edges.iter().map(|x| x.len()).sum::<usize>() / 2
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueueEntry {
+    cost: f32,
+    node: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost && self.node == other.node
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Probabilistic roadmap (Kavraki et al.). The roadmap outlives single queries: it is built once,
+/// then repaired from `Grid::changes_since` when walls move, and start and goal are only linked
+/// into it for the duration of a query.
+#[derive(Clone)]
+pub struct PrmStrategy {
+    pub samples: usize,
+    pub connection_radius: f32,
+    pub max_neighbours: usize,
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    seed: Option<u64>,
+    roadmap: Option<Roadmap>,
+    last_update: Option<RoadmapUpdate>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl PrmStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            samples: 400,
+            connection_radius: 150.0,
+            max_neighbours: 10,
+            agent_radius: 0.0,
+            grid,
+            seed: None,
+            roadmap: None,
+            last_update: None,
+            cancel_flag: None,
+        }
+    }
+
+    /// Takes effect the next time the roadmap is built from scratch.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    pub fn last_update(&self) -> Option<RoadmapUpdate> {
+        self.last_update
+    }
+
+    /// Alive nodes and undirected edges of the current roadmap.
+    pub fn roadmap_size(&self) -> (usize, usize) {
+        self.roadmap.as_ref().map_or((0, 0), |x| {
+            (x.alive.iter().filter(|x| **x).count(), x.edge_count())
+        })
+    }
+
+    pub fn roadmap_edges(&self) -> Vec<(Vec2, Vec2)> {
+        let Some(roadmap) = self.roadmap.as_ref() else {
+            return Vec::new();
+        };
+        roadmap
+            .edges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, edges)| {
+                edges
+                    .iter()
+                    .filter(move |x| i < x.0)
+                    .map(move |x| (roadmap.nodes[i], roadmap.nodes[x.0]))
+            })
+            .collect()
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        let grid = self.grid.lock().unwrap().clone();
+
+        let changes = self
+            .roadmap
+            .as_ref()
+            .filter(|x| x.agent_radius == self.agent_radius && x.samples == self.samples)
+            .and_then(|x| grid.changes_since(x.revision));
+        let mut roadmap = match (self.roadmap.take(), changes) {
+            (Some(roadmap), Some(changes)) if changes.is_empty() => {
+                self.last_update = Some(RoadmapUpdate::Reused);
+                roadmap
+            }
+            (Some(mut roadmap), Some(changes)) => {
+                let (removed, added) = self.repair(&grid, &mut roadmap, &changes);
+                self.last_update = Some(RoadmapUpdate::Repaired { removed, added });
+                roadmap
+            }
+            _ => {
+                let roadmap = self.build(&grid)?;
+                self.last_update = Some(RoadmapUpdate::Built);
+                roadmap
+            }
+        };
+        roadmap.revision = grid.revision();
+
+        let path = self.query(&grid, &roadmap, start, goal);
+        self.roadmap = Some(roadmap);
+        path
+    }
+
+    fn build(&self, grid: &Grid) -> Option<Roadmap> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        let mut roadmap = Roadmap {
+            nodes: Vec::new(),
+            alive: Vec::new(),
+            edges: Vec::new(),
+            revision: grid.revision(),
+            agent_radius: self.agent_radius,
+            samples: self.samples,
+        };
+
+        let size = Vec2::new(grid.world_width(), grid.world_height());
+        for _ in 0..self.samples * 20 {
+            if roadmap.nodes.len() >= self.samples {
+                break;
+            }
+            let pos = grid.root() + Vec2::new(rng.random_range(0.0..size.x), rng.random_range(0.0..size.y));
+            if self.is_free(grid, pos) {
+                roadmap.add_node(pos);
+            }
+        }
+
+        for i in 0..roadmap.nodes.len() {
+            if self.is_cancelled() {
+                return None;
+            }
+            self.connect(grid, &mut roadmap, i);
+        }
+        Some(roadmap)
+    }
+
+    /// Links `index` to its nearest visible neighbours within the connection radius.
+    fn connect(&self, grid: &Grid, roadmap: &mut Roadmap, index: usize) -> usize {
+        let pos = roadmap.nodes[index];
+        let mut added = 0;
+        let neighbours: Vec<usize> = roadmap
+            .by_distance(pos)
+            .into_iter()
+            .filter(|&x| x != index)
+            .take_while(|&x| roadmap.nodes[x].distance(pos) <= self.connection_radius)
+            .take(self.max_neighbours)
+            .collect();
+        for other in neighbours {
+            if !roadmap.is_linked(index, other) && self.is_clear(grid, pos, roadmap.nodes[other]) {
+                roadmap.link(index, other);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Drops nodes and edges that new walls cut, and samples and links again around removed walls.
+    /// Returns the number of edges removed and added.
+    fn repair(&self, grid: &Grid, roadmap: &mut Roadmap, changes: &[CellChange]) -> (usize, usize) {
+        let cells: HashSet<(usize, usize)> = changes.iter().map(|x| (x.x, x.y)).collect();
+        let mut removed = 0;
+        let mut freed = Vec::new();
+
+        let mut walled = Vec::new();
+        for &(x, y) in cells.iter() {
+            let Some(quad) = grid.get_cell_quad(x, y) else {
+                continue;
+            };
+            if grid.is_air(x, y) == Some(true) {
+                freed.push(quad.pos + quad.siz / 2.0);
+            } else {
+                walled.push(quad);
+            }
+        }
+
+        // Only nodes next to a new wall can have lost their clearance.
+        let near = self.agent_radius + grid.cell_size();
+        for i in 0..roadmap.nodes.len() {
+            let node = roadmap.nodes[i];
+            if roadmap.alive[i]
+                && walled.iter().any(|x| node.distance(x.pos + x.siz / 2.0) <= near)
+                && !self.is_free(grid, node)
+            {
+                roadmap.alive[i] = false;
+                for (other, _) in std::mem::take(&mut roadmap.edges[i]) {
+                    roadmap.edges[other].retain(|x| x.0 != i);
+                    removed += 1;
+                }
+            }
+        }
+
+        for quad in walled {
+            let min = quad.pos - Vec2::splat(self.agent_radius);
+            let max = quad.pos + quad.siz + Vec2::splat(self.agent_radius);
+            let overlaps = |a: Vec2, b: Vec2| {
+                a.min(b).cmple(max).all() && a.max(b).cmpge(min).all()
+            };
+
+            for i in 0..roadmap.nodes.len() {
+                let from = roadmap.nodes[i];
+                let cut: Vec<usize> = roadmap.edges[i]
+                    .iter()
+                    .map(|x| x.0)
+                    .filter(|&j| i < j && overlaps(from, roadmap.nodes[j]) && !self.is_clear(grid, from, roadmap.nodes[j]))
+                    .collect();
+                for j in cut {
+                    roadmap.edges[i].retain(|x| x.0 != j);
+                    roadmap.edges[j].retain(|x| x.0 != i);
+                    removed += 1;
+                }
+            }
+        }
+
+        // Nodes a passing wall killed come back once they are clear again, and freed space gets a
+        // node of its own unless an earlier repair already put one there.
+        for &center in freed.iter() {
+            for i in 0..roadmap.nodes.len() {
+                let node = roadmap.nodes[i];
+                if !roadmap.alive[i] && node.distance(center) <= near && self.is_free(grid, node) {
+                    roadmap.alive[i] = true;
+                }
+            }
+            if !roadmap.nodes.contains(&center) && self.is_free(grid, center) {
+                roadmap.add_node(center);
+            }
+        }
+
+        // Everything around freed space tries its neighbours again.
+        let mut added = 0;
+        let reach = self.connection_radius + grid.cell_size();
+        for center in freed {
+            for i in 0..roadmap.nodes.len() {
+                if roadmap.alive[i] && roadmap.nodes[i].distance(center) <= reach {
+                    added += self.connect(grid, roadmap, i);
+                }
+            }
+        }
+
+        // Dead nodes are kept so they can come back; only once they pile up are they dropped.
+        let dead = roadmap.alive.iter().filter(|x| !**x).count();
+        if dead > self.samples / 2 {
+            roadmap.compact();
+        }
+        (removed, added)
+    }
+
+    /// A* over the roadmap with start and goal linked in as two extra nodes.
+    fn query(&self, grid: &Grid, roadmap: &Roadmap, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        if self.is_clear(grid, start, goal) {
+            return Some(vec![start, goal]);
+        }
+
+        let links = |pos: Vec2| -> Vec<(usize, f32)> {
+            roadmap
+                .by_distance(pos)
+                .into_iter()
+                .filter(|&x| self.is_clear(grid, pos, roadmap.nodes[x]))
+                .take(self.max_neighbours)
+                .map(|x| (x, roadmap.nodes[x].distance(pos)))
+                .collect()
+        };
+        let start_links = links(start);
+        let goal_links = links(goal);
+
+        let count = roadmap.nodes.len();
+        let (start_node, goal_node) = (count, count + 1);
+        let position = |i: usize| match i {
+            _ if i == start_node => start,
+            _ if i == goal_node => goal,
+            _ => roadmap.nodes[i],
+        };
+
+        let mut cost = vec![f32::INFINITY; count + 2];
+        let mut parent = vec![usize::MAX; count + 2];
+        let mut open = BinaryHeap::new();
+        cost[start_node] = 0.0;
+        open.push(QueueEntry {
+            cost: start.distance(goal),
+            node: start_node,
+        });
+
+        while let Some(QueueEntry { node, .. }) = open.pop() {
+            if node == goal_node {
+                let mut path = vec![goal];
+                let mut current = goal_node;
+                while parent[current] != usize::MAX {
+                    current = parent[current];
+                    path.push(position(current));
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let edges: Box<dyn Iterator<Item = (usize, f32)>> = if node == start_node {
+                Box::new(start_links.iter().copied())
+            } else {
+                let to_goal = goal_links.iter().find(|x| x.0 == node).map(|x| (goal_node, x.1));
+                Box::new(roadmap.edges[node].iter().copied().chain(to_goal))
+            };
+            for (next, length) in edges {
+                let next_cost = cost[node] + length;
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    parent[next] = node;
+                    open.push(QueueEntry {
+                        cost: next_cost + position(next).distance(goal),
+                        node: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn is_free(&self, grid: &Grid, pos: Vec2) -> bool {
+        grid.get_from_world_pos(pos)
+            .is_some_and(|(x, y)| grid.is_air(x, y) == Some(true))
+            && grid.distance_to_obstacle(pos) > self.agent_radius
+    }
+
+    fn is_clear(&self, grid: &Grid, from: Vec2, to: Vec2) -> bool {
+        grid.is_segment_clear(from, to, self.agent_radius)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
@@ -0,0 +1,353 @@
+use crate::algorithm::grid::Grid;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RrtVariant {
+    /// One tree grown from the start, stops at the first connection to the goal.
+    Rrt,
+    /// Trees from both ends, each greedily connected to the other's newest node.
+    Connect,
+    /// One tree that picks the cheapest parent and rewires its neighbours, using the whole budget.
+    Star,
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode {
+    pos: Vec2,
+    parent: Option<usize>,
+    cost: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Tree {
+    nodes: Vec<TreeNode>,
+}
+
+impl Tree {
+    fn new(root: Vec2) -> Self {
+        Self {
+            nodes: vec![TreeNode {
+                pos: root,
+                parent: None,
+                cost: 0.0,
+            }],
+        }
+    }
+
+    fn nearest(&self, pos: Vec2) -> usize {
+        (0..self.nodes.len())
+            .min_by(|&a, &b| {
+                self.nodes[a]
+                    .pos
+                    .distance_squared(pos)
+                    .total_cmp(&self.nodes[b].pos.distance_squared(pos))
+            })
+            .unwrap()
+    }
+
+    fn near(&self, pos: Vec2, radius: f32) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].pos.distance_squared(pos) <= radius * radius)
+            .collect()
+    }
+
+    fn push(&mut self, pos: Vec2, parent: usize) -> usize {
+        let cost = self.nodes[parent].cost + self.nodes[parent].pos.distance(pos);
+        self.nodes.push(TreeNode {
+            pos,
+            parent: Some(parent),
+            cost,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Root first, `index` last.
+    fn path_to(&self, mut index: usize) -> Vec<Vec2> {
+        let mut path = vec![self.nodes[index].pos];
+        while let Some(parent) = self.nodes[index].parent {
+            path.push(self.nodes[parent].pos);
+            index = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.nodes
+            .iter()
+            .filter_map(|x| x.parent.map(|p| (self.nodes[p].pos, x.pos)))
+    }
+}
+
+/// Outcome of stepping a tree towards a point.
+enum Extend {
+    Trapped,
+    Advanced(usize),
+    Reached(usize),
+}
+
+/// Rapidly-exploring random trees (LaValle) in world space. Edges are checked against the
+/// walls by ray casting, widened by `agent_radius` when it is set.
+#[derive(Clone)]
+pub struct RrtStrategy {
+    pub variant: RrtVariant,
+    pub max_iterations: usize,
+    pub step_size: f32,
+    /// Chance of sampling the goal (the other tree's root for Connect) instead of a random point.
+    pub goal_bias: f64,
+    /// Neighbourhood RRT* chooses parents from and rewires.
+    pub rewire_radius: f32,
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    seed: Option<u64>,
+    tree_edges: Vec<(Vec2, Vec2)>,
+    iterations: usize,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl RrtStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            variant: RrtVariant::Rrt,
+            max_iterations: 3000,
+            step_size: 40.0,
+            goal_bias: 0.05,
+            rewire_radius: 90.0,
+            agent_radius: 0.0,
+            grid,
+            seed: None,
+            tree_edges: Vec::new(),
+            iterations: 0,
+            cancel_flag: None,
+        }
+    }
+
+    /// Each plan restarts from `seed`, so a seeded run always grows the same tree.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    /// Edges of the tree(s) grown by the last plan, parent first.
+    pub fn tree_edges(&self) -> &[(Vec2, Vec2)] {
+        &self.tree_edges
+    }
+
+    /// Samples drawn by the last plan.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        let grid = self.grid.lock().unwrap().clone();
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        self.iterations = 0;
+        self.tree_edges.clear();
+
+        if self.is_clear(&grid, start, goal) {
+            self.tree_edges.push((start, goal));
+            return Some(vec![start, goal]);
+        }
+
+        match self.variant {
+            RrtVariant::Rrt => self.plan_rrt(&grid, &mut rng, start, goal),
+            RrtVariant::Connect => self.plan_connect(&grid, &mut rng, start, goal),
+            RrtVariant::Star => self.plan_star(&grid, &mut rng, start, goal),
+        }
+    }
+
+    fn plan_rrt(&mut self, grid: &Grid, rng: &mut StdRng, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let mut tree = Tree::new(start);
+        let mut path = None;
+
+        while self.iterations < self.max_iterations && !self.is_cancelled() {
+            self.iterations += 1;
+            let sample = self.sample(grid, rng, goal);
+            let (Extend::Advanced(index) | Extend::Reached(index)) = self.extend(grid, &mut tree, sample) else {
+                continue;
+            };
+            let pos = tree.nodes[index].pos;
+            if pos.distance(goal) <= self.step_size && self.is_clear(grid, pos, goal) {
+                let goal_index = tree.push(goal, index);
+                path = Some(tree.path_to(goal_index));
+                break;
+            }
+        }
+
+        self.tree_edges.extend(tree.edges());
+        path
+    }
+
+    fn plan_connect(&mut self, grid: &Grid, rng: &mut StdRng, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let mut trees = [Tree::new(start), Tree::new(goal)];
+        let mut path = None;
+
+        while self.iterations < self.max_iterations && !self.is_cancelled() {
+            self.iterations += 1;
+            // The start tree (0) and the goal tree (1) take turns growing towards the sample.
+            let from_start = self.iterations % 2 == 1;
+            let target = trees[usize::from(from_start)].nodes[0].pos;
+            let sample = self.sample(grid, rng, target);
+
+            let [first, second] = &mut trees;
+            let (tree_a, tree_b) = if from_start { (first, second) } else { (second, first) };
+            let (Extend::Advanced(new) | Extend::Reached(new)) = self.extend(grid, tree_a, sample) else {
+                continue;
+            };
+            let new_pos = tree_a.nodes[new].pos;
+
+            let reached = loop {
+                match self.extend(grid, tree_b, new_pos) {
+                    Extend::Advanced(_) => continue,
+                    Extend::Reached(index) => break Some(index),
+                    Extend::Trapped => break None,
+                }
+            };
+            if let Some(joint) = reached {
+                let (start_end, goal_end) = if from_start { (new, joint) } else { (joint, new) };
+                // Both halves end on the same point, so the goal half drops it before joining.
+                let mut joined = trees[0].path_to(start_end);
+                let mut back = trees[1].path_to(goal_end);
+                back.pop();
+                back.reverse();
+                joined.extend(back);
+                path = Some(joined);
+                break;
+            }
+        }
+
+        for tree in trees.iter() {
+            self.tree_edges.extend(tree.edges());
+        }
+        path
+    }
+
+    fn plan_star(&mut self, grid: &Grid, rng: &mut StdRng, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let mut tree = Tree::new(start);
+        let mut children: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut goal_index: Option<usize> = None;
+
+        while self.iterations < self.max_iterations && !self.is_cancelled() {
+            self.iterations += 1;
+            let sample = self.sample(grid, rng, goal);
+            let nearest = tree.nearest(sample);
+            let pos = self.steer(tree.nodes[nearest].pos, sample);
+            if goal_index.is_some_and(|x| tree.nodes[x].pos == pos) || !self.is_clear(grid, tree.nodes[nearest].pos, pos) {
+                continue;
+            }
+
+            let near = tree.near(pos, self.rewire_radius);
+            let parent = near
+                .iter()
+                .copied()
+                .filter(|&i| self.is_clear(grid, tree.nodes[i].pos, pos))
+                .min_by(|&a, &b| {
+                    let cost = |i: usize| tree.nodes[i].cost + tree.nodes[i].pos.distance(pos);
+                    cost(a).total_cmp(&cost(b))
+                })
+                .unwrap_or(nearest);
+            let index = tree.push(pos, parent);
+            children.push(Vec::new());
+            children[parent].push(index);
+
+            for other in near {
+                let cost = tree.nodes[index].cost + pos.distance(tree.nodes[other].pos);
+                if cost + 1e-3 >= tree.nodes[other].cost || !self.is_clear(grid, pos, tree.nodes[other].pos) {
+                    continue;
+                }
+                let old_parent = tree.nodes[other].parent.unwrap();
+                children[old_parent].retain(|&x| x != other);
+                children[index].push(other);
+                tree.nodes[other].parent = Some(index);
+                let delta = tree.nodes[other].cost - cost;
+                Self::propagate_cost(&mut tree, &children, other, delta);
+            }
+
+            if pos == goal {
+                goal_index.get_or_insert(index);
+            } else if pos.distance(goal) <= self.step_size && self.is_clear(grid, pos, goal) {
+                let cost = tree.nodes[index].cost + pos.distance(goal);
+                match goal_index {
+                    Some(g) if cost + 1e-3 < tree.nodes[g].cost => {
+                        let old_parent = tree.nodes[g].parent.unwrap();
+                        children[old_parent].retain(|&x| x != g);
+                        children[index].push(g);
+                        tree.nodes[g].parent = Some(index);
+                        let delta = tree.nodes[g].cost - cost;
+                        Self::propagate_cost(&mut tree, &children, g, delta);
+                    }
+                    Some(_) => {}
+                    None => {
+                        goal_index = Some(tree.push(goal, index));
+                        children.push(Vec::new());
+                        children[index].push(tree.nodes.len() - 1);
+                    }
+                }
+            }
+        }
+
+        self.tree_edges.extend(tree.edges());
+        goal_index.map(|x| tree.path_to(x))
+    }
+
+    /// Lowers the cost of `index` and everything below it by `delta`.
+    fn propagate_cost(tree: &mut Tree, children: &[Vec<usize>], index: usize, delta: f32) {
+        let mut stack = vec![index];
+        while let Some(node) = stack.pop() {
+            tree.nodes[node].cost -= delta;
+            stack.extend(children[node].iter().copied());
+        }
+    }
+
+    /// Steps the tree's nearest node towards `target` by at most `step_size`.
+    fn extend(&self, grid: &Grid, tree: &mut Tree, target: Vec2) -> Extend {
+        let nearest = tree.nearest(target);
+        let from = tree.nodes[nearest].pos;
+        let pos = self.steer(from, target);
+        if pos == from || !self.is_clear(grid, from, pos) {
+            return Extend::Trapped;
+        }
+        let index = tree.push(pos, nearest);
+        if pos == target { Extend::Reached(index) } else { Extend::Advanced(index) }
+    }
+
+    fn steer(&self, from: Vec2, to: Vec2) -> Vec2 {
+        if from.distance(to) <= self.step_size {
+            to
+        } else {
+            from + (to - from).normalize() * self.step_size
+        }
+    }
+
+    fn sample(&self, grid: &Grid, rng: &mut StdRng, goal: Vec2) -> Vec2 {
+        if rng.random_bool(self.goal_bias.clamp(0.0, 1.0)) {
+            return goal;
+        }
+        let size = Vec2::new(grid.world_width(), grid.world_height());
+        grid.root() + Vec2::new(rng.random_range(0.0..size.x), rng.random_range(0.0..size.y))
+    }
+
+    fn is_clear(&self, grid: &Grid, from: Vec2, to: Vec2) -> bool {
+        grid.is_segment_clear(from, to, self.agent_radius)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
@@ -0,0 +1,250 @@
+use crate::algorithm::grid::Grid;
+use bevy::prelude::*;
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
+};
+
+/// Corners are pushed this far out of the wall (at least) so rays through them do not graze it.
+const CORNER_OFFSET: f32 = 0.5;
+
+#[derive(Debug, Clone)]
+struct VisibilityGraph {
+    nodes: Vec<Vec2>,
+    edges: Vec<Vec<(usize, f32)>>,
+    revision: u64,
+    agent_radius: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueueEntry {
+    cost: f32,
+    node: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost && self.node == other.node
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Shortest paths around axis-aligned walls. A shortest path only bends at convex wall corners,
+/// so A* over the graph of mutually visible corners is exact up to the corner offset.
+#[derive(Clone)]
+pub struct VisibilityGraphStrategy {
+    pub agent_radius: f32,
+
+    grid: Arc<Mutex<Grid>>,
+    graph: Option<VisibilityGraph>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl VisibilityGraphStrategy {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            agent_radius: 0.0,
+            grid,
+            graph: None,
+            cancel_flag: None,
+        }
+    }
+
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    pub fn graph_edges(&self) -> Vec<(Vec2, Vec2)> {
+        let Some(graph) = self.graph.as_ref() else {
+            return Vec::new();
+        };
+        graph
+            .edges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, edges)| {
+                edges
+                    .iter()
+                    .filter(move |x| i < x.0)
+                    .map(move |x| (graph.nodes[i], graph.nodes[x.0]))
+            })
+            .collect()
+    }
+
+    pub fn corner_count(&self) -> usize {
+        self.graph.as_ref().map_or(0, |x| x.nodes.len())
+    }
+
+    pub fn path_finding(&mut self, start: Option<Vec2>, goal: Option<Vec2>) -> Option<Vec<Vec2>> {
+        let start = start?;
+        let goal = goal?;
+        let grid = self.grid.lock().unwrap().clone();
+
+        let stale = self
+            .graph
+            .as_ref()
+            .is_none_or(|x| x.revision != grid.revision() || x.agent_radius != self.agent_radius);
+        if stale {
+            self.graph = None;
+            self.graph = Some(self.build(&grid)?);
+        }
+        self.query(&grid, self.graph.as_ref()?, start, goal)
+    }
+
+    /// Lattice points at a convex wall corner, pushed diagonally into the free cells that see it:
+    /// a free cell gets one when its two side neighbours around the point are open and the cell
+    /// across is a wall, or when two walls touch diagonally at the point, one on each side of it.
+    fn corners(&self, grid: &Grid) -> Vec<Vec2> {
+        let is_wall = |x: isize, y: isize| {
+            x >= 0 && y >= 0 && grid.is_wall(x as usize, y as usize) == Some(true)
+        };
+        let offset = self.agent_radius.max(CORNER_OFFSET);
+
+        let mut corners = Vec::new();
+        for y in 0..=grid.height() as isize {
+            for x in 0..=grid.width() as isize {
+                // Cells touching lattice point (x, y): (x-1, y-1), (x, y-1), (x-1, y), (x, y).
+                for (dx, dy) in [(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+                    if is_wall(x + dx, y + dy) {
+                        continue;
+                    }
+                    // The cell across the point, and the two next to this one.
+                    let across = is_wall(x - 1 - dx, y - 1 - dy);
+                    let sides = (is_wall(x - 1 - dx, y + dy), is_wall(x + dx, y - 1 - dy));
+                    if sides.0 != sides.1 || sides.0 == across {
+                        continue;
+                    }
+                    // Into this cell: its offset -1 maps to -1 and 0 maps to +1.
+                    let into = Vec2::new((2 * dx + 1) as f32, (2 * dy + 1) as f32);
+                    let pos = grid.root() + Vec2::new(x as f32, y as f32) * grid.cell_size() + into * offset;
+                    if self.is_free(grid, pos) {
+                        corners.push(pos);
+                    }
+                }
+            }
+        }
+        corners
+    }
+
+    fn build(&self, grid: &Grid) -> Option<VisibilityGraph> {
+        let nodes = self.corners(grid);
+        let mut edges = vec![Vec::new(); nodes.len()];
+        for i in 0..nodes.len() {
+            if self.is_cancelled() {
+                return None;
+            }
+            for j in i + 1..nodes.len() {
+                if self.is_clear(grid, nodes[i], nodes[j]) {
+                    let cost = nodes[i].distance(nodes[j]);
+                    edges[i].push((j, cost));
+                    edges[j].push((i, cost));
+                }
+            }
+        }
+
+        Some(VisibilityGraph {
+            nodes,
+            edges,
+            revision: grid.revision(),
+            agent_radius: self.agent_radius,
+        })
+    }
+
+    /// A* from start to goal, both linked to every corner they can see.
+    fn query(&self, grid: &Grid, graph: &VisibilityGraph, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        if self.is_clear(grid, start, goal) {
+            return Some(vec![start, goal]);
+        }
+
+        let visible = |pos: Vec2| -> Vec<(usize, f32)> {
+            (0..graph.nodes.len())
+                .filter(|&i| self.is_clear(grid, pos, graph.nodes[i]))
+                .map(|i| (i, graph.nodes[i].distance(pos)))
+                .collect()
+        };
+        let start_links = visible(start);
+        let goal_links = visible(goal);
+
+        let count = graph.nodes.len();
+        let (start_node, goal_node) = (count, count + 1);
+        let position = |i: usize| match i {
+            _ if i == start_node => start,
+            _ if i == goal_node => goal,
+            _ => graph.nodes[i],
+        };
+
+        let mut cost = vec![f32::INFINITY; count + 2];
+        let mut parent = vec![usize::MAX; count + 2];
+        let mut open = BinaryHeap::new();
+        cost[start_node] = 0.0;
+        open.push(QueueEntry {
+            cost: start.distance(goal),
+            node: start_node,
+        });
+
+        while let Some(QueueEntry { node, .. }) = open.pop() {
+            if node == goal_node {
+                let mut path = vec![goal];
+                let mut current = goal_node;
+                while parent[current] != usize::MAX {
+                    current = parent[current];
+                    path.push(position(current));
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let edges: Box<dyn Iterator<Item = (usize, f32)>> = if node == start_node {
+                Box::new(start_links.iter().copied())
+            } else {
+                let to_goal = goal_links.iter().find(|x| x.0 == node).map(|x| (goal_node, x.1));
+                Box::new(graph.edges[node].iter().copied().chain(to_goal))
+            };
+            for (next, length) in edges {
+                let next_cost = cost[node] + length;
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    parent[next] = node;
+                    open.push(QueueEntry {
+                        cost: next_cost + position(next).distance(goal),
+                        node: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn is_free(&self, grid: &Grid, pos: Vec2) -> bool {
+        grid.get_from_world_pos(pos)
+            .is_some_and(|(x, y)| grid.is_air(x, y) == Some(true))
+            && grid.distance_to_obstacle(pos) >= self.agent_radius
+    }
+
+    fn is_clear(&self, grid: &Grid, from: Vec2, to: Vec2) -> bool {
+        grid.is_segment_clear(from, to, self.agent_radius)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|x| x.load(AtomicOrdering::Relaxed))
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::algorithm::distance_field::GeodesicField;
+use crate::algorithm::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaypointOrder {
+    InOrder,
+    AnyOrder,
+}
+
+impl WaypointOrder {
+    pub fn next(&self) -> Self {
+        match self {
+            WaypointOrder::InOrder => WaypointOrder::AnyOrder,
+            WaypointOrder::AnyOrder => WaypointOrder::InOrder,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WaypointOrder::InOrder => "in order",
+            WaypointOrder::AnyOrder => "any order",
+        }
+    }
+}
+
+/// Ant colony for the open tour start -> every waypoint once -> goal.
+///
+/// Nodes are numbered start = 0, waypoints = 1..=n, goal = n + 1 in the distance matrix.
+#[derive(Debug, Clone)]
+pub struct TourAco {
+    pub ant_number: usize,
+    pub iterations: usize,
+    pub alpha: f64,
+    pub beta: f64,
+    pub evaporation: f64,
+    pub deposit: f64,
+    seed: Option<u64>,
+}
+
+impl TourAco {
+    pub fn new() -> Self {
+        Self {
+            ant_number: 20,
+            iterations: 100,
+            alpha: 1.0,
+            beta: 3.0,
+            evaporation: 0.1,
+            deposit: 1.0,
+            seed: None,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Waypoint indices (0-based) in visiting order.
+    pub fn solve(&self, distances: &[Vec<f32>]) -> Vec<usize> {
+        let waypoints = distances.len().saturating_sub(2);
+        if waypoints <= 1 {
+            return (0..waypoints).collect();
+        }
+        let goal = waypoints + 1;
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        let mut pheromones = vec![vec![1.0_f64; distances.len()]; distances.len()];
+        let mut best: Option<(f64, Vec<usize>)> = None;
+
+        for _ in 0..self.iterations {
+            let mut tours = Vec::with_capacity(self.ant_number);
+            for _ in 0..self.ant_number {
+                let mut tour = vec![0];
+                let mut remaining: Vec<usize> = (1..=waypoints).collect();
+                while !remaining.is_empty() {
+                    let current = *tour.last().unwrap();
+                    let weights: Vec<f64> = remaining
+                        .iter()
+                        .map(|&x| {
+                            let visibility = 1.0 / (distances[current][x] as f64 + 1.0);
+                            pheromones[current][x].powf(self.alpha) * visibility.powf(self.beta)
+                        })
+                        .collect();
+                    let total: f64 = weights.iter().sum();
+
+                    // Unreachable waypoints have zero weight everywhere; fall back to a uniform pick.
+                    let pick = if total > 0.0 {
+                        let mut roll = rng.random_range(0.0..total);
+                        weights
+                            .iter()
+                            .position(|&w| {
+                                roll -= w;
+                                roll < 0.0
+                            })
+                            .unwrap_or(weights.len() - 1)
+                    } else {
+                        rng.random_range(0..remaining.len())
+                    };
+                    tour.push(remaining.swap_remove(pick));
+                }
+                tour.push(goal);
+
+                let length: f64 = tour.windows(2).map(|x| distances[x[0]][x[1]] as f64).sum();
+                if best.as_ref().is_none_or(|x| length < x.0) {
+                    best = Some((length, tour.clone()));
+                }
+                tours.push((length, tour));
+            }
+
+            for row in pheromones.iter_mut() {
+                for value in row.iter_mut() {
+                    *value *= 1.0 - self.evaporation;
+                }
+            }
+            for (length, tour) in tours.iter().chain(best.iter()) {
+                if !length.is_finite() || *length <= 0.0 {
+                    continue;
+                }
+                for edge in tour.windows(2) {
+                    pheromones[edge[0]][edge[1]] += self.deposit / length;
+                }
+            }
+        }
+
+        best.map(|(_, tour)| tour[1..tour.len() - 1].iter().map(|x| x - 1).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Travel distance between every pair of stops; infinite where walls separate them.
+pub fn stop_distances(grid: &Grid, stops: &[Vec2]) -> Vec<Vec<f32>> {
+    let fields: Vec<Option<GeodesicField>> = stops.iter().map(|x| GeodesicField::from_goal(grid, *x)).collect();
+    stops
+        .iter()
+        .map(|from| {
+            fields
+                .iter()
+                .map(|field| {
+                    field
+                        .as_ref()
+                        .and_then(|x| x.distance_at(*from))
+                        .unwrap_or(f32::INFINITY)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Which stops can reach each other: stops with the same label share an open region, `None` marks
+/// stops in a wall or off the grid. Labels count up in stop order, so equal inputs give equal output.
+pub fn stop_regions(grid: &Grid, stops: &[Vec2]) -> Vec<Option<usize>> {
+    let (width, height) = (grid.width(), grid.height());
+    let mut region = vec![usize::MAX; width * height];
+    let mut count = 0;
+    stops
+        .iter()
+        .map(|stop| {
+            let (x, y) = grid.get_from_world_pos(*stop)?;
+            if grid.is_air(x, y) != Some(true) {
+                return None;
+            }
+            if region[y * width + x] == usize::MAX {
+                // Diagonal steps never cut corners, so 4-connected regions are the reachable ones.
+                let mut queue = vec![(x, y)];
+                region[y * width + x] = count;
+                while let Some((cx, cy)) = queue.pop() {
+                    let next = [
+                        (cx + 1, cy),
+                        (cx.wrapping_sub(1), cy),
+                        (cx, cy + 1),
+                        (cx, cy.wrapping_sub(1)),
+                    ];
+                    for (nx, ny) in next {
+                        if nx < width
+                            && ny < height
+                            && region[ny * width + nx] == usize::MAX
+                            && grid.is_air(nx, ny) == Some(true)
+                        {
+                            region[ny * width + nx] = count;
+                            queue.push((nx, ny));
+                        }
+                    }
+                }
+                count += 1;
+            }
+            Some(region[y * width + x])
+        })
+        .collect()
+}
+
+/// Start, the waypoints in the order they will be visited, then the goal.
+pub fn order_stops(
+    grid: &Grid,
+    start: Vec2,
+    waypoints: &[Vec2],
+    goal: Vec2,
+    order: WaypointOrder,
+    tour: &TourAco,
+) -> Vec<Vec2> {
+    let mut stops = vec![start];
+    stops.extend_from_slice(waypoints);
+    stops.push(goal);
+
+    if order == WaypointOrder::AnyOrder && waypoints.len() > 1 {
+        let visit = tour.solve(&stop_distances(grid, &stops));
+        stops = std::iter::once(start)
+            .chain(visit.into_iter().map(|x| waypoints[x]))
+            .chain(std::iter::once(goal))
+            .collect();
+    }
+    stops
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowState {
+    Moving,
+    Waiting,
+    Arrived,
+}
+
+/// Drives its entity along the hybrid path at `AgentSettings::speed`.
+#[derive(Component)]
+pub struct PathFollower {
+    pub route: Vec<Vec2>,
+    pub next: usize,
+    pub source: Option<Vec<Vec2>>,
+    pub origin: Vec2,
+    pub heading: f32,
+    pub state: FollowState,
+    pub elapsed: f32,
+    pub travelled: f32,
+    /// Waypoints already driven through on this run.
+    pub visited: Vec<Vec2>,
+}
+
+impl PathFollower {
+    pub fn new(origin: Vec2) -> Self {
+        Self {
+            route: Vec::new(),
+            next: 0,
+            source: None,
+            origin,
+            heading: 0.0,
+            state: FollowState::Waiting,
+            elapsed: 0.0,
+            travelled: 0.0,
+            visited: Vec::new(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AgentTrail {
+    pub capacity: usize,
+    pub points: Vec<Vec2>,
+}
+
+impl Default for AgentTrail {
+    fn default() -> Self {
+        Self {
+            capacity: 2000,
+            points: Vec::new(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct AgentSettings {
+    pub speed: f32,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        Self { speed: 120.0 }
+    }
+}
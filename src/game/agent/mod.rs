@@ -0,0 +1,18 @@
+mod component;
+mod system;
+
+use crate::game::control::GameState;
+use bevy::prelude::*;
+use component::AgentSettings;
+use system::{follow_path, handle_agent_input, render_agent};
+
+pub struct AgentPlugin;
+
+impl Plugin for AgentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AgentSettings>()
+            .add_systems(Update, handle_agent_input.run_if(in_state(GameState::Idle)))
+            .add_systems(FixedUpdate, follow_path)
+            .add_systems(Update, render_agent);
+    }
+}
@@ -0,0 +1,188 @@
+use super::component::{AgentSettings, AgentTrail, FollowState, PathFollower};
+use crate::algorithm::grid::Grid;
+use crate::algorithm::path::path_length;
+use crate::game::algorithm_resource::AlgorithmResource;
+use bevy::prelude::*;
+
+pub fn handle_agent_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut settings: ResMut<AgentSettings>,
+    agent_query: Query<Entity, With<PathFollower>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyA) {
+        if agent_query.is_empty() {
+            let Some(start) = algorithm_resource.problem.start else {
+                println!("Set a start point before spawning the agent");
+                return;
+            };
+            commands.spawn((
+                PathFollower::new(start),
+                AgentTrail::default(),
+                Transform::from_translation(start.extend(0.6)),
+            ));
+            println!("Agent spawned");
+        } else {
+            for entity in agent_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            println!("Agent removed");
+        }
+    }
+
+    let step = if keyboard_input.just_pressed(KeyCode::Equal) {
+        20.0
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        -20.0
+    } else {
+        return;
+    };
+    settings.speed = (settings.speed + step).clamp(20.0, 600.0);
+    println!("Agent speed: {:.0}", settings.speed);
+}
+
+pub fn follow_path(
+    time: Res<Time>,
+    settings: Res<AgentSettings>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut agent_query: Query<(&mut Transform, &mut PathFollower, &mut AgentTrail)>,
+) {
+    let Some(start) = algorithm_resource.problem.start else {
+        return;
+    };
+    let radius = algorithm_resource.problem.agent_radius;
+    let waypoints = &algorithm_resource.problem.waypoints;
+    let grid = algorithm_resource.grid.lock().unwrap();
+
+    for (mut transform, mut follower, mut trail) in agent_query.iter_mut() {
+        let mut pos = transform.translation.truncate();
+
+        // A new start point restarts the run from there.
+        if follower.origin != start {
+            *follower = PathFollower::new(start);
+            trail.points.clear();
+            pos = start;
+        }
+        if follower.source != algorithm_resource.path {
+            let path = algorithm_resource.path.clone();
+            reroute(&mut follower, path, pos, waypoints, &grid, radius);
+        }
+        if follower.state == FollowState::Arrived || follower.route.is_empty() {
+            continue;
+        }
+
+        follower.elapsed += time.delta_secs();
+        let mut budget = settings.speed * time.delta_secs();
+        while budget > 0.0 && follower.next < follower.route.len() {
+            let target = follower.route[follower.next];
+            // Something moved onto the way ahead; hold position until a replan clears it.
+            if !grid.is_segment_clear(pos, target, radius) {
+                follower.state = FollowState::Waiting;
+                break;
+            }
+            follower.state = FollowState::Moving;
+
+            let distance = pos.distance(target);
+            if distance > 0.0 {
+                follower.heading = f32::atan2(target.y - pos.y, target.x - pos.x);
+            }
+            if distance <= budget {
+                pos = target;
+                budget -= distance;
+                follower.travelled += distance;
+                follower.next += 1;
+                if waypoints.contains(&target) && !follower.visited.contains(&target) {
+                    follower.visited.push(target);
+                }
+            } else {
+                pos += (target - pos) / distance * budget;
+                follower.travelled += budget;
+                budget = 0.0;
+            }
+        }
+
+        if follower.next >= follower.route.len() {
+            follower.state = FollowState::Arrived;
+            println!(
+                "Agent arrived: travelled {:.1} in {:.2} s (planned length {:.1})",
+                follower.travelled,
+                follower.elapsed,
+                follower.source.as_deref().map_or(0.0, path_length)
+            );
+        }
+
+        if trail.points.last().is_none_or(|x| x.distance(pos) > 2.0) {
+            if trail.points.len() >= trail.capacity {
+                trail.points.remove(0);
+            }
+            trail.points.push(pos);
+        }
+        transform.translation = pos.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(follower.heading);
+    }
+}
+
+/// Joins a new path at the furthest point the agent can see, so it never backtracks to the start,
+/// but never beyond the next waypoint it has not driven through yet.
+fn reroute(
+    follower: &mut PathFollower,
+    path: Option<Vec<Vec2>>,
+    pos: Vec2,
+    waypoints: &[Vec2],
+    grid: &Grid,
+    radius: f32,
+) {
+    follower.source = path.clone();
+    let Some(path) = path else {
+        follower.route.clear();
+        follower.next = 0;
+        follower.state = FollowState::Waiting;
+        return;
+    };
+
+    let last = path
+        .iter()
+        .position(|x| waypoints.contains(x) && !follower.visited.contains(x))
+        .unwrap_or(path.len().saturating_sub(1));
+    let visible = (0..=last)
+        .rev()
+        .find(|&i| grid.is_segment_clear(pos, path[i], radius));
+    let closest = (0..=last)
+        .min_by(|&a, &b| pos.distance(path[a]).total_cmp(&pos.distance(path[b])))
+        .unwrap_or(0);
+
+    // Refinements of a path that still ends here do not restart a finished run.
+    if follower.state == FollowState::Arrived && path.last().is_some_and(|x| x.distance(pos) < 1.0) {
+        follower.next = path.len();
+        follower.route = path;
+        return;
+    }
+
+    follower.next = visible.unwrap_or(closest);
+    follower.route = path;
+    if follower.state == FollowState::Arrived {
+        follower.state = FollowState::Moving;
+    }
+}
+
+pub fn render_agent(
+    mut gizmos: Gizmos,
+    algorithm_resource: Res<AlgorithmResource>,
+    agent_query: Query<(&Transform, &PathFollower, &AgentTrail)>,
+) {
+    let radius = algorithm_resource.problem.agent_radius.max(8.0);
+
+    for (transform, follower, trail) in agent_query.iter() {
+        let pos = transform.translation.truncate();
+        let color = match follower.state {
+            FollowState::Moving => Color::srgb(1.0, 1.0, 0.3),
+            FollowState::Waiting => Color::srgb(1.0, 0.3, 0.3),
+            FollowState::Arrived => Color::srgb(0.3, 1.0, 0.4),
+        };
+
+        gizmos.linestrip_2d(trail.points.iter().copied(), Color::srgba(1.0, 1.0, 0.3, 0.5));
+        gizmos.circle_2d(Isometry2d::from_translation(pos), radius, color);
+        gizmos.line_2d(pos, pos + Vec2::from_angle(follower.heading) * radius * 1.8, color);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::algorithm::{grid::Grid, problem::Problem};
+use crate::algorithm::{grid::Grid, post_process::PostProcessReport, problem::Problem};
 use bevy::prelude::*;
 use std::sync::{Arc, Mutex};
 
@@ -8,4 +8,8 @@ pub struct AlgorithmResource {
     pub problem: Problem,
     pub path: Option<Vec<bevy::prelude::Vec2>>,
     pub astar_path: Option<Vec<bevy::prelude::Vec2>>,
+    pub compare_path: Option<Vec<bevy::prelude::Vec2>>,
+    pub path_report: Option<PostProcessReport>,
+    pub astar_path_report: Option<PostProcessReport>,
+    pub compare_path_report: Option<PostProcessReport>,
 }
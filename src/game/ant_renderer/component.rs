@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+#[derive(Resource)]
+pub struct AntTrailPlayback {
+    pub enabled: bool,
+    pub playing: bool,
+    pub selected: Option<u64>,
+    pub progress: f32,
+    pub steps_per_second: f32,
+}
+
+impl Default for AntTrailPlayback {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            playing: true,
+            selected: None,
+            progress: 0.0,
+            steps_per_second: 60.0,
+        }
+    }
+}
+
+impl AntTrailPlayback {
+    pub fn restart(&mut self) {
+        self.progress = 0.0;
+    }
+}
@@ -0,0 +1,16 @@
+mod component;
+mod system;
+
+pub use component::AntTrailPlayback;
+
+use bevy::prelude::*;
+use system::{handle_ant_trail_input, render_ant_trails};
+
+pub struct AntTrailPlugin;
+
+impl Plugin for AntTrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AntTrailPlayback>()
+            .add_systems(Update, (handle_ant_trail_input, render_ant_trails).chain());
+    }
+}
@@ -0,0 +1,127 @@
+use super::component::AntTrailPlayback;
+use crate::algorithm::solve::hybrid::{AntStatus, AntTour, ColonyIteration, HybridStrategy};
+use crate::game::pathfinding_system::PathfindingStrategy;
+use bevy::prelude::*;
+
+pub fn handle_ant_trail_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    strategy_resource: Res<PathfindingStrategy>,
+    mut playback: ResMut<AntTrailPlayback>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        playback.enabled = !playback.enabled;
+        playback.selected = None;
+        playback.restart();
+        println!("Ant trails {}", if playback.enabled { "on" } else { "off" });
+    }
+
+    if !playback.enabled {
+        return;
+    }
+
+    let history = strategy_resource.hybrid_strategy.tour_history();
+    let (Some(first), Some(last)) = (history.front(), history.back()) else {
+        return;
+    };
+    let cur = playback.selected.unwrap_or(last.index).clamp(first.index, last.index);
+
+    let stepped = if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        Some(cur.saturating_sub(1).max(first.index))
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        Some((cur + 1).min(last.index))
+    } else {
+        None
+    };
+    if let Some(index) = stepped {
+        playback.selected = Some(index);
+        playback.restart();
+        println!("Ant trails: iteration {}", index);
+    } else if keyboard_input.just_pressed(KeyCode::End) {
+        playback.selected = None;
+        playback.restart();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        playback.playing = !playback.playing;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        playback.steps_per_second *= 2.0;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        playback.steps_per_second = (playback.steps_per_second / 2.0).max(1.0);
+    }
+}
+
+pub fn render_ant_trails(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    strategy_resource: Res<PathfindingStrategy>,
+    mut playback: ResMut<AntTrailPlayback>,
+) {
+    if !playback.enabled {
+        return;
+    }
+
+    let hybrid = &strategy_resource.hybrid_strategy;
+    let history = hybrid.tour_history();
+    let iteration: Option<&ColonyIteration> = match playback.selected {
+        Some(index) => history.iter().find(|x| x.index == index),
+        None => history.back(),
+    };
+    let Some(iteration) = iteration else {
+        return;
+    };
+
+    let longest = iteration.tours.iter().map(|x| x.path.len()).max().unwrap_or(0);
+    if playback.playing {
+        playback.progress += playback.steps_per_second * time.delta_secs();
+        // Following the newest iteration loops the animation, a selected one holds at the end.
+        if playback.progress > longest as f32 {
+            playback.progress = if playback.selected.is_none() { 0.0 } else { longest as f32 };
+        }
+    }
+    let step = playback.progress as usize;
+
+    for tour in iteration.tours.iter() {
+        if tour.path.is_empty() {
+            continue;
+        }
+
+        let shown = (step + 1).min(tour.path.len());
+        let color = ant_color(hybrid, tour);
+        gizmos.linestrip_2d(tour.path[..shown].iter().copied(), color.with_alpha(0.6));
+
+        let head = tour.path[shown - 1];
+        let finished = shown == tour.path.len();
+        match tour.status {
+            AntStatus::Dead if finished => {
+                gizmos.cross_2d(Isometry2d::from_translation(head), 6.0, Color::srgb(1.0, 0.0, 0.0));
+            }
+            AntStatus::Stuck if finished => {
+                gizmos.circle_2d(Isometry2d::from_translation(head), 5.0, Color::srgb(0.5, 0.5, 0.5));
+            }
+            _ => {
+                gizmos.circle_2d(Isometry2d::from_translation(head), 4.0, color);
+            }
+        }
+    }
+}
+
+fn ant_color(hybrid: &HybridStrategy, tour: &AntTour) -> Color {
+    let normalize = |value: f64, min: f64, max: f64| {
+        if max > min {
+            ((value - min) / (max - min)).clamp(0.0, 1.0) as f32
+        } else {
+            0.5
+        }
+    };
+
+    // Alpha picks the hue and beta the lightness, so ants sharing a particle share a color.
+    let alpha = normalize(tour.alpha, hybrid.init_alpha_min, hybrid.init_alpha_max);
+    let beta = normalize(tour.beta, hybrid.init_beta_min, hybrid.init_beta_max);
+    let color = Color::hsl(300.0 * alpha, 0.9, 0.35 + 0.4 * beta);
+
+    match tour.status {
+        AntStatus::Arrived => color,
+        AntStatus::Stuck | AntStatus::Dead => color.with_alpha(0.35),
+    }
+}
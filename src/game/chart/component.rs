@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceSample {
+    pub hybrid_len: Option<f32>,
+    pub a_star_len: Option<f32>,
+    /// Whichever comparison solver is selected, if any.
+    pub compare_len: Option<f32>,
+    pub hybrid_ms: f64,
+    pub a_star_ms: f64,
+    pub compare_ms: f64,
+}
+
+#[derive(Resource)]
+pub struct ConvergenceHistory {
+    pub capacity: usize,
+    pub paused: bool,
+    pub samples: VecDeque<ConvergenceSample>,
+}
+
+#[derive(Component)]
+pub struct ChartLabel;
+
+impl Default for ConvergenceHistory {
+    fn default() -> Self {
+        Self {
+            capacity: 300,
+            paused: false,
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl ConvergenceHistory {
+    pub fn push(&mut self, sample: ConvergenceSample) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn latest(&self) -> Option<&ConvergenceSample> {
+        self.samples.back()
+    }
+}
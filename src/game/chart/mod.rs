@@ -0,0 +1,20 @@
+mod component;
+mod system;
+
+pub use component::ConvergenceHistory;
+
+use crate::game::pathfinding_system::update_pathfinding;
+use bevy::prelude::*;
+use system::{handle_chart_input, record_convergence, render_chart, setup_chart_label, update_chart_label};
+
+pub struct ChartPlugin;
+
+impl Plugin for ChartPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConvergenceHistory>()
+            .add_systems(Startup, setup_chart_label)
+            .add_systems(Update, record_convergence.after(update_pathfinding))
+            .add_systems(Update, handle_chart_input)
+            .add_systems(Update, (render_chart, update_chart_label));
+    }
+}
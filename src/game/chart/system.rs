@@ -0,0 +1,259 @@
+use super::component::{ChartLabel, ConvergenceHistory, ConvergenceSample};
+use crate::algorithm::path::path_length;
+use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::control::RunMode;
+use crate::game::pathfinding_system::{ComparisonSolver, PathfindingStrategy, PlanningTask};
+use crate::game::timer::AlgorithmTimers;
+use bevy::prelude::*;
+
+const PANEL_MARGIN: f32 = 10.0;
+const PANEL_SIZE: Vec2 = Vec2::new(300.0, 100.0);
+const PANEL_GAP: f32 = 10.0;
+const LABEL_HEIGHT: f32 = 80.0;
+
+const HYBRID_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+const A_STAR_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const COMPARE_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+const FRAME_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+
+pub fn setup_chart_label(mut commands: Commands) {
+    commands.spawn((
+        ChartLabel,
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(FRAME_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(PANEL_MARGIN),
+            right: Val::Px(PANEL_MARGIN),
+            width: Val::Px(PANEL_SIZE.x),
+            ..default()
+        },
+    ));
+}
+
+pub fn record_convergence(
+    strategy_resource: Res<PathfindingStrategy>,
+    algorithm_resource: Res<AlgorithmResource>,
+    timers: Res<AlgorithmTimers>,
+    planning_task: Res<PlanningTask>,
+    mut history: ResMut<ConvergenceHistory>,
+    mut last_completed: Local<u64>,
+) {
+    // One sample per finished plan, which is one colony iteration of the hybrid.
+    if planning_task.completed == *last_completed {
+        return;
+    }
+    *last_completed = planning_task.completed;
+
+    if history.paused {
+        return;
+    }
+
+    let hybrid_len = strategy_resource.hybrid_strategy.global_best_len();
+    let comparing = strategy_resource.comparison != ComparisonSolver::Off;
+    history.push(ConvergenceSample {
+        hybrid_len: hybrid_len.is_finite().then_some(hybrid_len as f32),
+        a_star_len: algorithm_resource.astar_path.as_deref().map(path_length),
+        compare_len: algorithm_resource.compare_path.as_deref().map(path_length).filter(|_| comparing),
+        hybrid_ms: timers.hybrid_last_ms,
+        a_star_ms: timers.a_star_last_ms,
+        compare_ms: if comparing { timers.compare_last_ms } else { 0.0 },
+    });
+}
+
+pub fn handle_chart_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<ConvergenceHistory>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyP) {
+        history.paused = !history.paused;
+        println!("Chart {}", if history.paused { "paused" } else { "resumed" });
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyX) {
+        history.clear();
+        println!("Chart cleared");
+    }
+}
+
+pub fn render_chart(
+    mut gizmos: Gizmos,
+    history: Res<ConvergenceHistory>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+) {
+    let Some((camera, camera_transform)) = camera_q.iter().next() else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    // Panels are laid out in viewport pixels under the label, then mapped into world space.
+    let panel_left = viewport_size.x - PANEL_MARGIN - PANEL_SIZE.x;
+    let len_top = PANEL_MARGIN + LABEL_HEIGHT;
+    let time_top = len_top + PANEL_SIZE.y + PANEL_GAP;
+
+    let len_max = history
+        .samples
+        .iter()
+        .flat_map(|x| [x.hybrid_len, x.a_star_len, x.compare_len])
+        .flatten()
+        .fold(0.0, f32::max);
+    let time_max = history
+        .samples
+        .iter()
+        .map(|x| x.hybrid_ms.max(x.a_star_ms).max(x.compare_ms) as f32)
+        .fold(0.0, f32::max);
+
+    let to_world = |pos: Vec2| camera.viewport_to_world_2d(camera_transform, pos).ok();
+
+    let len_panel = Vec2::new(panel_left, len_top);
+    let time_panel = Vec2::new(panel_left, time_top);
+
+    for panel in [len_panel, time_panel] {
+        draw_frame(&mut gizmos, panel, &to_world);
+    }
+
+    draw_series(
+        &mut gizmos,
+        len_panel,
+        history.capacity,
+        len_max,
+        history.samples.iter().map(|x| x.a_star_len),
+        A_STAR_COLOR,
+        &to_world,
+    );
+    draw_series(
+        &mut gizmos,
+        len_panel,
+        history.capacity,
+        len_max,
+        history.samples.iter().map(|x| x.compare_len),
+        COMPARE_COLOR,
+        &to_world,
+    );
+    draw_series(
+        &mut gizmos,
+        len_panel,
+        history.capacity,
+        len_max,
+        history.samples.iter().map(|x| x.hybrid_len),
+        HYBRID_COLOR,
+        &to_world,
+    );
+    draw_series(
+        &mut gizmos,
+        time_panel,
+        history.capacity,
+        time_max,
+        history.samples.iter().map(|x| Some(x.a_star_ms as f32)),
+        A_STAR_COLOR,
+        &to_world,
+    );
+    draw_series(
+        &mut gizmos,
+        time_panel,
+        history.capacity,
+        time_max,
+        history.samples.iter().map(|x| (x.compare_ms > 0.0).then_some(x.compare_ms as f32)),
+        COMPARE_COLOR,
+        &to_world,
+    );
+    draw_series(
+        &mut gizmos,
+        time_panel,
+        history.capacity,
+        time_max,
+        history.samples.iter().map(|x| Some(x.hybrid_ms as f32)),
+        HYBRID_COLOR,
+        &to_world,
+    );
+}
+
+pub fn update_chart_label(
+    history: Res<ConvergenceHistory>,
+    run_mode: Res<State<RunMode>>,
+    mut label_query: Query<&mut Text, With<ChartLabel>>,
+) {
+    let fmt_len = |len: Option<f32>| len.map_or("-".to_string(), |x| format!("{:.1}", x));
+
+    let mut content = match history.latest() {
+        Some(sample) => format!(
+            "Best length: hybrid {} ; A* {} ; compare {}\nFrame time: hybrid {:.3} ms ; A* {:.3} ms ; compare {:.3} ms",
+            fmt_len(sample.hybrid_len),
+            fmt_len(sample.a_star_len),
+            fmt_len(sample.compare_len),
+            sample.hybrid_ms,
+            sample.a_star_ms,
+            sample.compare_ms,
+        ),
+        None => "Best length: -\nFrame time: -".to_string(),
+    };
+    content.push_str(&format!(
+        "\nIterations: {}{} ; run: {:?}\n[P] pause  [X] clear",
+        history.samples.len(),
+        if history.paused { " (paused)" } else { "" },
+        run_mode.get(),
+    ));
+
+    for mut text in label_query.iter_mut() {
+        text.0 = content.clone();
+    }
+}
+
+fn draw_frame(gizmos: &mut Gizmos, top_left: Vec2, to_world: &impl Fn(Vec2) -> Option<Vec2>) {
+    let corners = [
+        top_left,
+        top_left + Vec2::new(PANEL_SIZE.x, 0.0),
+        top_left + PANEL_SIZE,
+        top_left + Vec2::new(0.0, PANEL_SIZE.y),
+        top_left,
+    ];
+    if let Some(points) = corners.into_iter().map(to_world).collect::<Option<Vec<Vec2>>>() {
+        gizmos.linestrip_2d(points, FRAME_COLOR);
+    }
+}
+
+fn draw_series(
+    gizmos: &mut Gizmos,
+    top_left: Vec2,
+    capacity: usize,
+    max: f32,
+    values: impl Iterator<Item = Option<f32>>,
+    color: Color,
+    to_world: &impl Fn(Vec2) -> Option<Vec2>,
+) {
+    if max <= 0.0 || capacity < 2 {
+        return;
+    }
+
+    let step = PANEL_SIZE.x / (capacity - 1) as f32;
+    let mut segment: Vec<Vec2> = Vec::new();
+
+    for (i, value) in values.enumerate() {
+        let point = value.and_then(|x| {
+            to_world(Vec2::new(
+                top_left.x + i as f32 * step,
+                top_left.y + PANEL_SIZE.y * (1.0 - x / max),
+            ))
+        });
+
+        // Missing samples split the series so gaps stay visible.
+        match point {
+            Some(point) => segment.push(point),
+            None => {
+                if segment.len() >= 2 {
+                    gizmos.linestrip_2d(segment.drain(..), color);
+                }
+                segment.clear();
+            }
+        }
+    }
+
+    if segment.len() >= 2 {
+        gizmos.linestrip_2d(segment, color);
+    }
+}
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 pub struct ClickPosition {
     pub grid_x: Option<usize>,
     pub grid_y: Option<usize>,
+    pub agent_start: Option<Vec2>,
 }
 
 impl Default for ClickPosition {
@@ -11,6 +12,7 @@ impl Default for ClickPosition {
         Self {
             grid_x: None,
             grid_y: None,
+            agent_start: None,
         }
     }
 }
\ No newline at end of file
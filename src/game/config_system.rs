@@ -0,0 +1,119 @@
+use crate::algorithm::config::{PresetTarget, SolverConfig, SolverPreset};
+use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::pathfinding_system::PathfindingStrategy;
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+#[derive(Resource)]
+pub struct SolverConfigResource {
+    pub path: PathBuf,
+    pub config: Option<SolverConfig>,
+    pub active: Option<String>,
+    /// The settings the solvers started with; every preset is laid over these.
+    defaults: Option<SolverPreset>,
+}
+
+impl Default for SolverConfigResource {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("config/solvers.ron"),
+            config: None,
+            active: None,
+            defaults: None,
+        }
+    }
+}
+
+impl SolverConfigResource {
+    pub fn reload(&mut self) -> Result<(), String> {
+        let config = SolverConfig::load(&self.path)?;
+        // Keep the preset picked with F6 across reloads when it still exists.
+        let active = self
+            .active
+            .take()
+            .filter(|x| config.preset(x).is_some())
+            .unwrap_or_else(|| config.active.clone());
+        self.active = Some(active);
+        self.config = Some(config);
+        Ok(())
+    }
+
+    pub fn apply(
+        &mut self,
+        strategy: &mut PathfindingStrategy,
+        algorithm_resource: &mut AlgorithmResource,
+    ) -> Result<(), String> {
+        let (Some(config), Some(active)) = (&self.config, &self.active) else {
+            return Err("no solver config loaded".to_string());
+        };
+        let preset = config
+            .preset(active)
+            .ok_or_else(|| format!("preset \"{}\" is not defined", active))?;
+
+        let comparison = &mut strategy.comparison_strategies;
+        let mut target = PresetTarget {
+            astar: &mut strategy.astar_strategy,
+            hybrid: &mut strategy.hybrid_strategy,
+            rrt: &mut comparison.rrt,
+            prm: &mut comparison.prm,
+            genetic: &mut comparison.genetic,
+            hpa: &mut comparison.hpa,
+            agent_radius: &mut algorithm_resource.problem.agent_radius,
+        };
+        let defaults = self
+            .defaults
+            .get_or_insert_with(|| SolverPreset::capture(&target));
+        preset.over(defaults).apply(&mut target)?;
+
+        let max_radius = algorithm_resource.grid.lock().unwrap().cell_size() / 2.0;
+        let problem = &mut algorithm_resource.problem;
+        problem.agent_radius = problem.agent_radius.min(max_radius);
+        Ok(())
+    }
+}
+
+pub fn load_solver_config(
+    mut config_resource: ResMut<SolverConfigResource>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+    mut algorithm_resource: ResMut<AlgorithmResource>,
+) {
+    let result = config_resource
+        .reload()
+        .and_then(|_| config_resource.apply(&mut strategy_resource, &mut algorithm_resource));
+    match result {
+        Ok(()) => println!(
+            "Solver preset \"{}\" loaded from {} (seed: {:?})",
+            config_resource.active.as_deref().unwrap_or_default(),
+            config_resource.path.display(),
+            strategy_resource.hybrid_strategy.seed()
+        ),
+        Err(e) => println!("Solver config not applied: {}", e),
+    }
+}
+
+pub fn handle_config_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut config_resource: ResMut<SolverConfigResource>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+    mut algorithm_resource: ResMut<AlgorithmResource>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        load_solver_config(config_resource, strategy_resource, algorithm_resource);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        let Some(config) = &config_resource.config else {
+            println!("Solver config not loaded, press F5 to reload");
+            return;
+        };
+        let current = config_resource.active.as_deref().unwrap_or(&config.active);
+        let next = config.next_preset_name(current).to_string();
+        config_resource.active = Some(next.clone());
+
+        match config_resource.apply(&mut strategy_resource, &mut algorithm_resource) {
+            Ok(()) => println!("Solver preset \"{}\" applied", next),
+            Err(e) => println!("Solver preset \"{}\" not applied: {}", next, e),
+        }
+    }
+}
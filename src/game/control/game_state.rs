@@ -8,9 +8,16 @@ pub enum GameState {
     SetGoal,
     PlaceMode,
     DeleteMode,
+    SetAgentStart,
+    SetAgentGoal,
+    SetWaypoint,
+    PaintTerrain,
     Cancel,
     DoneStart,
     DoneGoal,
     DonePlace,
     DoneDelete,
+    DoneAgent,
+    DoneWaypoint,
+    DoneTerrain,
 }
@@ -1,7 +1,18 @@
-use crate::game::control::GameState;
+use crate::algorithm::problem::AgentGoal;
+use crate::game::control::{GameState, RunControl, RunMode};
 use crate::game::click_position::ClickPosition;
+use crate::game::pathfinding_system::{PathfindingStrategy, PlanningTask};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
+/// What a click on the grid needs to know about the cursor: the window, the camera and the UI under it.
+#[derive(SystemParam)]
+pub struct PointerQueries<'w, 's> {
+    windows: Query<'w, 's, &'static Window>,
+    camera_q: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    ui_query: Query<'w, 's, &'static Interaction>,
+}
+
 pub fn handle_keyboard_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     current_state: Res<State<GameState>>,
@@ -22,9 +33,15 @@ pub fn handle_keyboard_input(
                 next_state.set(GameState::PlaceMode);
             } else if keyboard_input.just_pressed(KeyCode::KeyD) {
                 next_state.set(GameState::DeleteMode);
+            } else if keyboard_input.just_pressed(KeyCode::Digit3) {
+                next_state.set(GameState::SetAgentStart);
+            } else if keyboard_input.just_pressed(KeyCode::KeyW) {
+                next_state.set(GameState::SetWaypoint);
+            } else if keyboard_input.just_pressed(KeyCode::KeyB) {
+                next_state.set(GameState::PaintTerrain);
             }
         }
-        GameState::SetStart | GameState::SetGoal => {
+        GameState::SetStart | GameState::SetGoal | GameState::SetAgentStart | GameState::SetAgentGoal | GameState::SetWaypoint => {
             if keyboard_input.just_pressed(KeyCode::Digit1) {
                 next_state.set(GameState::SetStart);
             } else if keyboard_input.just_pressed(KeyCode::Digit2) {
@@ -33,9 +50,15 @@ pub fn handle_keyboard_input(
                 next_state.set(GameState::PlaceMode);
             } else if keyboard_input.just_pressed(KeyCode::KeyD) {
                 next_state.set(GameState::DeleteMode);
+            } else if keyboard_input.just_pressed(KeyCode::Digit3) {
+                next_state.set(GameState::SetAgentStart);
+            } else if keyboard_input.just_pressed(KeyCode::KeyW) {
+                next_state.set(GameState::SetWaypoint);
+            } else if keyboard_input.just_pressed(KeyCode::KeyB) {
+                next_state.set(GameState::PaintTerrain);
             }
         }
-        GameState::PlaceMode | GameState::DeleteMode => {
+        GameState::PlaceMode | GameState::DeleteMode | GameState::PaintTerrain => {
             if keyboard_input.just_pressed(KeyCode::Digit1) {
                 next_state.set(GameState::SetStart);
             } else if keyboard_input.just_pressed(KeyCode::Digit2) {
@@ -44,25 +67,257 @@ pub fn handle_keyboard_input(
                 next_state.set(GameState::PlaceMode);
             } else if keyboard_input.just_pressed(KeyCode::KeyD) {
                 next_state.set(GameState::DeleteMode);
+            } else if keyboard_input.just_pressed(KeyCode::Digit3) {
+                next_state.set(GameState::SetAgentStart);
+            } else if keyboard_input.just_pressed(KeyCode::KeyW) {
+                next_state.set(GameState::SetWaypoint);
+            } else if keyboard_input.just_pressed(KeyCode::KeyB) {
+                next_state.set(GameState::PaintTerrain);
             }
         }
         _ => {}
     }
 }
 
+pub fn handle_run_mode_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut run_control: ResMut<RunControl>,
+    mut next_run_mode: ResMut<NextState<RunMode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        next_run_mode.set(RunMode::Continuous);
+        println!("Run: continuous");
+    } else if keyboard_input.just_pressed(KeyCode::KeyU) {
+        run_control.criterion.reset();
+        next_run_mode.set(RunMode::UntilConverged);
+        println!(
+            "Run: until no improvement for {} iterations",
+            run_control.criterion.patience
+        );
+    } else if keyboard_input.just_pressed(KeyCode::KeyN) {
+        run_control.remaining = run_control.iterations;
+        next_run_mode.set(RunMode::Iterations);
+        println!("Run: {} iterations", run_control.iterations);
+    } else if keyboard_input.just_pressed(KeyCode::KeyS) {
+        next_run_mode.set(RunMode::Step);
+        println!("Run: single step");
+    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        next_run_mode.set(RunMode::Paused);
+        println!("Run: paused");
+    }
+}
+
+pub fn handle_post_process_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+) {
+    let post_processor = &mut strategy_resource.post_processor;
+    if keyboard_input.just_pressed(KeyCode::KeyJ) {
+        post_processor.prune = !post_processor.prune;
+        println!("Path pruning {}", if post_processor.prune { "on" } else { "off" });
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyK) {
+        post_processor.smoothing = post_processor.smoothing.next();
+        println!("Path smoothing: {:?}", post_processor.smoothing);
+    }
+}
+
+pub fn handle_comparison_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        strategy_resource.comparison = strategy_resource.comparison.next();
+        println!("Comparison solver: {}", strategy_resource.comparison.name());
+    }
+}
+
+pub fn handle_waypoint_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
+) {
+    let problem = &mut algorithm_resource.problem;
+    if keyboard_input.just_pressed(KeyCode::KeyI) && problem.waypoints.pop().is_some() {
+        println!("Removed last waypoint ({} left)", problem.waypoints.len());
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyY) {
+        problem.waypoint_order = problem.waypoint_order.next();
+        println!("Waypoints: {}", problem.waypoint_order.name());
+    }
+}
+
+pub fn handle_pareto_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+) {
+    let hybrid = &mut strategy_resource.hybrid_strategy;
+    if keyboard_input.just_pressed(KeyCode::KeyV) {
+        if !hybrid.pareto && !algorithm_resource.problem.waypoints.is_empty() {
+            println!("Pareto front is not available with waypoints, remove them first");
+            return;
+        }
+        hybrid.pareto = !hybrid.pareto;
+        hybrid.pareto_choice = 0;
+        println!("Pareto front {}", if hybrid.pareto { "on" } else { "off" });
+    }
+    if !hybrid.pareto {
+        return;
+    }
+
+    let count = hybrid.pareto_front().tours().len();
+    if count == 0 {
+        return;
+    }
+    let choice = hybrid.pareto_choice.min(count - 1);
+    let choice = if keyboard_input.just_pressed(KeyCode::Period) {
+        (choice + 1) % count
+    } else if keyboard_input.just_pressed(KeyCode::Comma) {
+        (choice + count - 1) % count
+    } else {
+        return;
+    };
+
+    // Only the shown tour changes, so a running plan carries on.
+    let grid = algorithm_resource.grid.clone();
+    let radius = algorithm_resource.problem.agent_radius;
+    let (path, report) = strategy_resource.select_pareto_tour(choice, &grid.lock().unwrap(), radius);
+    algorithm_resource.path = path;
+    algorithm_resource.path_report = report;
+
+    let hybrid = &strategy_resource.hybrid_strategy;
+    let tour = &hybrid.pareto_front().tours()[choice];
+    println!(
+        "Pareto tour {}/{}: length {:.1} ; turns {} ; turning {:.2} rad ; min clearance {:.1} ; terrain {:.1} ; cost {:.1}",
+        hybrid.pareto_choice + 1,
+        count,
+        tour.metrics.length,
+        tour.metrics.turns,
+        tour.metrics.turning_angle,
+        tour.metrics.min_clearance,
+        tour.metrics.terrain,
+        tour.cost
+    );
+}
+
+pub fn handle_heuristic_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyL) {
+        let hybrid = &mut strategy_resource.hybrid_strategy;
+        hybrid.geodesic_heuristic = !hybrid.geodesic_heuristic;
+        println!(
+            "Hybrid heuristic: {}",
+            if hybrid.geodesic_heuristic { "geodesic" } else { "straight-line" }
+        );
+    }
+}
+
+pub fn handle_seed_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    algorithm_resource: Res<crate::game::algorithm_resource::AlgorithmResource>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+    mut planning_task: ResMut<PlanningTask>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Digit8) {
+        // The comparison solver's path when one is shown, A*'s otherwise.
+        let (name, path) = match algorithm_resource.compare_path.as_ref() {
+            Some(path) => (strategy_resource.comparison.name(), Some(path)),
+            None => ("A*", algorithm_resource.astar_path.as_ref()),
+        };
+        match path {
+            None => println!("No {} path to seed the hybrid from", name),
+            Some(_) if !algorithm_resource.problem.waypoints.is_empty() => {
+                println!("Hybrid not seeded: seeding only covers a plan without waypoints");
+            }
+            Some(path) => {
+                // A running plan would hand back the colony from before the seed.
+                planning_task.cancel();
+                let hybrid = &mut strategy_resource.hybrid_strategy;
+                hybrid.agent_radius = algorithm_resource.problem.agent_radius;
+                if hybrid.seed_pheromones(path) {
+                    println!("Hybrid seeded from the {} path: it is now its best tour", name);
+                } else {
+                    println!("Hybrid seeded from the {} path: pheromone laid, its best tour is kept", name);
+                }
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit9) {
+        let hybrid = &mut strategy_resource.hybrid_strategy;
+        hybrid.warm_start = !hybrid.warm_start;
+        println!(
+            "Hybrid after wall edits: {}",
+            if hybrid.warm_start { "reroute the best tour (warm start)" } else { "forget the best tour" }
+        );
+    }
+}
+
+/// Where F7 saves the hybrid's pheromone and F8 loads it from.
+const PHEROMONE_FILE: &str = "pheromones.ron";
+
+pub fn handle_pheromone_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+    mut planning_task: ResMut<PlanningTask>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Digit0) {
+        let hybrid = &mut strategy_resource.hybrid_strategy;
+        hybrid.set_pheromone_policy(hybrid.pheromone_policy().next());
+        println!("Pheromone on start/goal change: {:?}", hybrid.pheromone_policy());
+    }
+    if keyboard_input.just_pressed(KeyCode::F7) {
+        match strategy_resource.hybrid_strategy.save_pheromones(PHEROMONE_FILE) {
+            Ok(count) => println!("Saved {} pheromone trails to {}", count, PHEROMONE_FILE),
+            Err(e) => println!("Pheromone not saved: {}", e),
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        // A plan still running would hand back the colony it started from.
+        planning_task.cancel();
+        match strategy_resource.hybrid_strategy.load_pheromones(PHEROMONE_FILE) {
+            Ok(count) => println!("Loaded {} pheromone trails from {}", count, PHEROMONE_FILE),
+            Err(e) => println!("Pheromone not loaded: {}", e),
+        }
+    }
+}
+
+pub fn handle_agent_radius_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
+) {
+    let step = if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        2.5
+    } else if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        -2.5
+    } else {
+        return;
+    };
+
+    let max_radius = algorithm_resource.grid.lock().unwrap().cell_size() / 2.0;
+    let problem = &mut algorithm_resource.problem;
+    problem.agent_radius = (problem.agent_radius + step).clamp(0.0, max_radius);
+    println!("Agent radius: {:.1}", problem.agent_radius);
+}
+
 pub fn handle_mouse_selection(
     mouse_input: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
+    pointer: PointerQueries,
     mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
     current_state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut click_position: ResMut<ClickPosition>,
 ) {
+    // Clicks on a UI panel belong to the panel, not the grid underneath it.
+    if pointer.ui_query.iter().any(|x| *x != Interaction::None) {
+        return;
+    }
+
     if mouse_input.just_pressed(MouseButton::Left) {
-        let window = windows.single();
+        let window = pointer.windows.single();
         let cursor_screen_pos = window.unwrap().cursor_position().unwrap();
-        let (camera, camera_transform) = camera_q.iter().next().unwrap();
+        let (camera, camera_transform) = pointer.camera_q.iter().next().unwrap();
         let ray = camera
             .viewport_to_world(camera_transform, cursor_screen_pos)
             .unwrap();
@@ -101,6 +356,62 @@ pub fn handle_mouse_selection(
                     println!("Delete wall at grid: ({}, {})", grid_x, grid_y);
                 }
             }
+            GameState::PaintTerrain => {
+                if let Some((grid_x, grid_y)) = algorithm_resource.grid.lock().unwrap().get_from_world_pos(world_pos) {
+                    click_position.grid_x = Some(grid_x);
+                    click_position.grid_y = Some(grid_y);
+                    next_state.set(GameState::DoneTerrain);
+                }
+            }
+            GameState::SetAgentStart | GameState::SetAgentGoal => {
+                let grid = algorithm_resource.grid.lock().unwrap();
+                let Some((grid_x, grid_y)) = grid.get_from_world_pos(world_pos) else {
+                    return;
+                };
+                if grid.is_air(grid_x, grid_y) != Some(true) {
+                    return;
+                }
+                let quad = grid.get_cell_quad(grid_x, grid_y).unwrap();
+                let cell_center = quad.pos + quad.siz / 2.0;
+                drop(grid);
+
+                // The MAPF solvers need every start and every goal on its own cell.
+                let agents = &algorithm_resource.problem.agents;
+                if *current_state.get() == GameState::SetAgentStart {
+                    if agents.iter().any(|x| x.start == cell_center) {
+                        println!("Another agent already starts at grid: ({}, {}), pick another cell", grid_x, grid_y);
+                        return;
+                    }
+                    click_position.agent_start = Some(cell_center);
+                    next_state.set(GameState::SetAgentGoal);
+                    println!("Agent start at grid: ({}, {}), now click its goal", grid_x, grid_y);
+                } else if agents.iter().any(|x| x.goal == cell_center) {
+                    println!("Another agent already ends at grid: ({}, {}), pick another cell", grid_x, grid_y);
+                } else if let Some(start) = click_position.agent_start.take() {
+                    algorithm_resource.problem.agents.push(AgentGoal {
+                        start,
+                        goal: cell_center,
+                    });
+                    next_state.set(GameState::DoneAgent);
+                    println!("Agent goal at grid: ({}, {})", grid_x, grid_y);
+                }
+            }
+            GameState::SetWaypoint => {
+                let grid = algorithm_resource.grid.lock().unwrap();
+                let Some((grid_x, grid_y)) = grid.get_from_world_pos(world_pos) else {
+                    return;
+                };
+                if grid.is_air(grid_x, grid_y) != Some(true) {
+                    return;
+                }
+                let quad = grid.get_cell_quad(grid_x, grid_y).unwrap();
+                let cell_center = quad.pos + quad.siz / 2.0;
+                drop(grid);
+
+                algorithm_resource.problem.waypoints.push(cell_center);
+                next_state.set(GameState::DoneWaypoint);
+                println!("Waypoint at grid: ({}, {})", grid_x, grid_y);
+            }
             _ => {}
         }
     }
@@ -1,29 +1,51 @@
 mod game_state;
 mod input;
+mod run_mode;
 mod system;
 
 pub use game_state::GameState;
+pub use run_mode::{RunControl, RunMode};
 
 use input::*;
 use system::*;
 
 use bevy::prelude::*;
 use crate::game::click_position::ClickPosition;
+use crate::game::pathfinding_system::update_pathfinding;
 
 pub struct ControlPlugin;
 
 impl Plugin for ControlPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ClickPosition>()
+            .init_resource::<RunControl>()
+            .init_state::<RunMode>()
             .add_systems(Update, handle_keyboard_input)
+            .add_systems(Update, handle_run_mode_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_post_process_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_agent_radius_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_heuristic_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_seed_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_pheromone_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_comparison_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_waypoint_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, handle_pareto_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, advance_run_mode.after(update_pathfinding))
             .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::SetStart)))
             .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::SetGoal)))
             .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::PlaceMode)))
             .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::DeleteMode)))
+            .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::SetAgentStart)))
+            .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::SetAgentGoal)))
+            .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::SetWaypoint)))
+            .add_systems(Update, handle_mouse_selection.run_if(in_state(GameState::PaintTerrain)))
             .add_systems(OnEnter(GameState::Cancel), on_cancel)
             .add_systems(OnEnter(GameState::DoneStart), on_done_start)
             .add_systems(OnEnter(GameState::DoneGoal), on_done_goal)
             .add_systems(OnEnter(GameState::DonePlace), on_done_place)
-            .add_systems(OnEnter(GameState::DoneDelete), on_done_delete);
+            .add_systems(OnEnter(GameState::DoneDelete), on_done_delete)
+            .add_systems(OnEnter(GameState::DoneAgent), on_done_agent)
+            .add_systems(OnEnter(GameState::DoneWaypoint), on_done_waypoint)
+            .add_systems(OnEnter(GameState::DoneTerrain), on_done_terrain);
     }
 }
@@ -0,0 +1,39 @@
+use crate::algorithm::solve::anytime::StoppingCriterion;
+use bevy::prelude::*;
+
+#[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub enum RunMode {
+    #[default]
+    Continuous,
+    UntilConverged,
+    Iterations,
+    Step,
+    Paused,
+}
+
+#[derive(Resource)]
+pub struct RunControl {
+    pub iterations: u64,
+    pub remaining: u64,
+    pub criterion: StoppingCriterion,
+}
+
+impl Default for RunControl {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            remaining: 0,
+            criterion: StoppingCriterion::new(50, None),
+        }
+    }
+}
+
+impl RunControl {
+    pub fn should_run(&self, mode: &RunMode) -> bool {
+        match mode {
+            RunMode::Continuous | RunMode::UntilConverged | RunMode::Step => true,
+            RunMode::Iterations => self.remaining > 0,
+            RunMode::Paused => false,
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use crate::game::control::GameState;
-use crate::game::pathfinding_system::{PathfindingStrategy, reset_pathfinding};
+use crate::game::control::{GameState, RunControl, RunMode};
+use crate::game::pathfinding_system::{PathfindingStrategy, PlanningTask, reset_pathfinding};
 use crate::game::solve_renderer::{GoalPoint, StartPoint, render_start_goal};
 use crate::game::click_position::ClickPosition;
+use crate::game::obstacle::ObstacleCells;
 use crate::game::timer::AlgorithmTimers;
 use crate::algorithm::grid::GridCell;
 use bevy::prelude::*;
@@ -16,6 +17,45 @@ pub fn on_cancel(
     println!("Cancel");
 }
 
+pub fn advance_run_mode(
+    strategy_resource: Res<PathfindingStrategy>,
+    planning_task: Res<PlanningTask>,
+    run_mode: Res<State<RunMode>>,
+    mut run_control: ResMut<RunControl>,
+    mut next_run_mode: ResMut<NextState<RunMode>>,
+    mut last_completed: Local<u64>,
+) {
+    if planning_task.completed == *last_completed {
+        return;
+    }
+    *last_completed = planning_task.completed;
+
+    let best_len = strategy_resource.hybrid_strategy.global_best_len();
+    let converged = run_control.criterion.update(best_len);
+
+    match run_mode.get() {
+        RunMode::Step => {
+            next_run_mode.set(RunMode::Paused);
+        }
+        RunMode::Iterations => {
+            run_control.remaining = run_control.remaining.saturating_sub(1);
+            if run_control.remaining == 0 {
+                next_run_mode.set(RunMode::Paused);
+                println!("Run: finished {} iterations, best {:.1}", run_control.iterations, best_len);
+            }
+        }
+        RunMode::UntilConverged if converged => {
+            next_run_mode.set(RunMode::Paused);
+            println!(
+                "Run: converged at {:.1} ({} iterations without improvement)",
+                best_len,
+                run_control.criterion.stale_iterations()
+            );
+        }
+        _ => {}
+    }
+}
+
 pub fn on_done_start(
     commands: Commands,
     algorithm_resource: Res<crate::game::algorithm_resource::AlgorithmResource>,
@@ -53,27 +93,76 @@ pub fn on_done_goal(
 pub fn on_done_place(
     mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
     click_position: Res<ClickPosition>,
+    obstacle_cells: Res<ObstacleCells>,
+    mut planning_task: ResMut<PlanningTask>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     if let (Some(grid_x), Some(grid_y)) = (click_position.grid_x, click_position.grid_y) {
-        if grid_x > 0 && grid_x < 19 && grid_y > 0 && grid_y < 14 {
+        if obstacle_cells.stamped.contains_key(&(grid_x, grid_y)) {
+            println!("Grid ({}, {}) is covered by a moving obstacle, wall not placed", grid_x, grid_y);
+        } else if grid_x > 0 && grid_x < 19 && grid_y > 0 && grid_y < 14 {
             let mut grid = algorithm_resource.grid.lock().unwrap();
             grid.set(grid_x, grid_y, GridCell::Wall);
+            // A plan started before the edit could run straight through the new wall.
+            planning_task.cancel();
             println!("Placed wall at grid: ({}, {})", grid_x, grid_y);
         }
     }
     next_state.set(GameState::Idle);
 }
 
+pub fn on_done_agent(
+    algorithm_resource: Res<crate::game::algorithm_resource::AlgorithmResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::Idle);
+    println!("Added MAPF agent ({} total)", algorithm_resource.problem.agents.len());
+}
+
+pub fn on_done_waypoint(
+    algorithm_resource: Res<crate::game::algorithm_resource::AlgorithmResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_state.set(GameState::Idle);
+    println!("Added waypoint ({} total)", algorithm_resource.problem.waypoints.len());
+}
+
+/// Cost multiplier of painted terrain; clicking a painted cell clears it again.
+const ROUGH_TERRAIN: f32 = 3.0;
+
+pub fn on_done_terrain(
+    algorithm_resource: Res<crate::game::algorithm_resource::AlgorithmResource>,
+    click_position: Res<ClickPosition>,
+    mut planning_task: ResMut<PlanningTask>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let (Some(grid_x), Some(grid_y)) = (click_position.grid_x, click_position.grid_y) {
+        let mut grid = algorithm_resource.grid.lock().unwrap();
+        if let Some(cost) = grid.terrain(grid_x, grid_y) {
+            let cost = if cost > 1.0 { 1.0 } else { ROUGH_TERRAIN };
+            grid.set_terrain(grid_x, grid_y, cost);
+            planning_task.cancel();
+            println!("Terrain cost {:.1} at grid: ({}, {})", cost, grid_x, grid_y);
+        }
+    }
+    next_state.set(GameState::Idle);
+}
+
 pub fn on_done_delete(
     mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
     click_position: Res<ClickPosition>,
+    obstacle_cells: Res<ObstacleCells>,
+    mut planning_task: ResMut<PlanningTask>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     if let (Some(grid_x), Some(grid_y)) = (click_position.grid_x, click_position.grid_y) {
-        if grid_x > 0 && grid_x < 19 && grid_y > 0 && grid_y < 14 {
+        // The obstacle clears its cells when it moves on, so the wall under it is not the user's.
+        if obstacle_cells.stamped.contains_key(&(grid_x, grid_y)) {
+            println!("Grid ({}, {}) is covered by a moving obstacle, nothing deleted", grid_x, grid_y);
+        } else if grid_x > 0 && grid_x < 19 && grid_y > 0 && grid_y < 14 {
             let mut grid = algorithm_resource.grid.lock().unwrap();
             grid.set(grid_x, grid_y, GridCell::Air);
+            planning_task.cancel();
             println!("Deleted wall at grid: ({}, {})", grid_x, grid_y);
         }
     }
@@ -1,7 +1,156 @@
+use crate::algorithm::path::path_length;
+use crate::algorithm::post_process::PostProcessReport;
+use crate::algorithm::solve::hpa::HpaBenchmark;
+use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::pathfinding_system::{ComparisonSolver, PathfindingStrategy};
 use crate::game::timer::AlgorithmTimers;
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
 
-pub fn log_timing_info(timers: Res<AlgorithmTimers>) {
+pub fn log_timing_info(
+    timers: Res<AlgorithmTimers>,
+    algorithm_resource: Res<AlgorithmResource>,
+    strategy_resource: Res<PathfindingStrategy>,
+) {
     timers.log_timings();
+
+    let comparison = strategy_resource.comparison;
+    if comparison != ComparisonSolver::Off {
+        timers.log_compare_timings(comparison.name());
+    }
+    let strategies = &strategy_resource.comparison_strategies;
+    match comparison {
+        ComparisonSolver::DStarLite => {
+            println!("D* Lite: expanded {} cells", strategies.dstar.expansions());
+        }
+        ComparisonSolver::Rrt | ComparisonSolver::RrtConnect | ComparisonSolver::RrtStar => {
+            println!(
+                "{}: {} samples ; {} tree edges",
+                comparison.name(),
+                strategies.rrt.iterations(),
+                strategies.rrt.tree_edges().len()
+            );
+        }
+        ComparisonSolver::Prm => {
+            let (nodes, edges) = strategies.prm.roadmap_size();
+            println!(
+                "PRM: roadmap {} nodes, {} edges ; last update: {:?}",
+                nodes,
+                edges,
+                strategies.prm.last_update()
+            );
+        }
+        ComparisonSolver::VisibilityGraph => {
+            println!(
+                "Visibility graph: {} corners, {} edges",
+                strategies.visibility.corner_count(),
+                strategies.visibility.graph_edges().len()
+            );
+            // Post-processing bends the visibility path away from the shortest one, so measure it raw.
+            let optimum = match algorithm_resource.compare_path_report {
+                Some(report) => Some(report.raw_len),
+                None => algorithm_resource.compare_path.as_deref().map(path_length),
+            };
+            if let Some(optimum) = optimum {
+                log_suboptimality(&algorithm_resource, optimum);
+            }
+        }
+        ComparisonSolver::Genetic => {
+            if let Some(stats) = strategies.genetic.history().back() {
+                println!(
+                    "GA: generation {} ; best fitness {:.1} ; mean {:.1} ; {} feasible ; global best {:.1}",
+                    strategies.genetic.generation(),
+                    stats.best,
+                    stats.mean,
+                    stats.feasible,
+                    strategies.genetic.global_best_len()
+                );
+            }
+        }
+        ComparisonSolver::Hpa => {
+            let (nodes, edges) = strategies.hpa.graph_size();
+            println!(
+                "HPA*: abstract graph {} nodes, {} edges ; expanded {} ; last update: {:?}",
+                nodes,
+                edges,
+                strategies.hpa.expansions(),
+                strategies.hpa.last_update()
+            );
+        }
+        ComparisonSolver::Off => {}
+    }
+
+    let reports = [
+        ("AStar", algorithm_resource.astar_path_report),
+        ("Hybrid", algorithm_resource.path_report),
+        (comparison.name(), algorithm_resource.compare_path_report),
+    ];
+    for (name, report) in reports {
+        if let Some(report) = report {
+            log_post_process(name, report);
+        }
+    }
+}
+
+/// Times HPA* against flat A* on generated maps far larger than the scene, off the main thread.
+pub fn run_hpa_benchmark(keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if !keyboard_input.just_pressed(KeyCode::Digit7) {
+        return;
+    }
+    println!("HPA* benchmark started");
+    AsyncComputeTaskPool::get()
+        .spawn(async move {
+            for size in [64, 128, 256, 512, 1024, 2048] {
+                let result = HpaBenchmark::run(size, 10, 1);
+                let fmt_len = |len: Option<f32>| len.map_or("-".to_string(), |x| format!("{:.0}", x));
+                println!(
+                    "HPA* benchmark {0}x{0}: A* {1:.1} ms (length {2}) ; HPA* build {3:.1} ms, query {4:.2} ms (length {5}), {6:.0}x faster per query ; one wall edit rebuilt {7} clusters in {8:.2} ms",
+                    result.size,
+                    result.a_star_ms,
+                    fmt_len(result.a_star_len),
+                    result.build_ms,
+                    result.query_ms,
+                    fmt_len(result.hpa_len),
+                    result.a_star_ms / result.query_ms.max(1e-3),
+                    result.rebuilt_clusters,
+                    result.rebuild_ms
+                );
+            }
+        })
+        .detach();
+}
+
+/// How much longer the hybrid and A* paths are than the visibility graph's shortest path.
+fn log_suboptimality(algorithm_resource: &AlgorithmResource, optimum: f32) {
+    if optimum <= 0.0 {
+        return;
+    }
+    let paths = [
+        ("AStar", algorithm_resource.astar_path.as_deref()),
+        ("Hybrid", algorithm_resource.path.as_deref()),
+    ];
+    for (name, path) in paths {
+        if let Some(path) = path {
+            let length = path_length(path);
+            println!(
+                "{}: {:.1} vs optimum {:.1} ({:+.1}%)",
+                name,
+                length,
+                optimum,
+                (length / optimum - 1.0) * 100.0
+            );
+        }
+    }
+}
+
+fn log_post_process(name: &str, report: PostProcessReport) {
+    println!(
+        "{}: length raw: {:.1} ; pruned: {:.1} ; final: {:.1}{}",
+        name,
+        report.raw_len,
+        report.pruned_len,
+        report.final_len,
+        if report.smoothing_rejected { " (smoothing collided, kept pruned path)" } else { "" }
+    );
 }
 
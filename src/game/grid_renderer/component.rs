@@ -6,6 +6,7 @@ use crate::algorithm::grid::Grid;
 #[derive(Component)]
 pub struct GridRenderer {
     pub wall_color: Color,
+    pub terrain_color: Color,
     pub grid: Grid,
 }
 
@@ -13,6 +14,7 @@ impl GridRenderer {
     pub fn new(grid: Grid, wall_color: Color) -> Self {
         Self {
             wall_color,
+            terrain_color: Color::srgb(0.3, 0.45, 0.2),
             grid,
         }
     }
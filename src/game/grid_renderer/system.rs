@@ -25,9 +25,17 @@ pub fn render_grid(
 
         for y in 0..grid.height() {
             for x in 0..grid.width() {
-                if let Some(GridCell::Air) = grid.get(x, y) {
-                    continue;
-                }
+                // Rough ground is shaded darker the more it costs to cross.
+                let color = match grid.get(x, y) {
+                    Some(GridCell::Wall) => grid_renderer.wall_color,
+                    Some(GridCell::Air) => match grid.terrain(x, y) {
+                        Some(cost) if cost > 1.0 => grid_renderer
+                            .terrain_color
+                            .with_alpha((0.25 * cost).min(0.9)),
+                        _ => continue,
+                    },
+                    None => continue,
+                };
                 if let Some(quad) = grid.get_cell_quad(x, y) {
                     let center_x = quad.pos.x + quad.siz.x / 2.0;
                     let center_y = quad.pos.y + quad.siz.y / 2.0;
@@ -35,7 +43,7 @@ pub fn render_grid(
                     commands.entity(entity).with_children(|parent| {
                         parent.spawn((
                             Sprite {
-                                color: grid_renderer.wall_color,
+                                color,
                                 custom_size: Some(Vec2::new(quad.siz.x, quad.siz.y)),
                                 ..default()
                             },
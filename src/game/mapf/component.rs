@@ -0,0 +1,64 @@
+use crate::algorithm::mapf::MapfSolution;
+use crate::algorithm::problem::AgentGoal;
+use bevy::prelude::*;
+use bevy::tasks::Task;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapfSolverKind {
+    Cbs,
+    MultiColonyAco,
+}
+
+impl MapfSolverKind {
+    pub fn next(&self) -> Self {
+        match self {
+            MapfSolverKind::Cbs => MapfSolverKind::MultiColonyAco,
+            MapfSolverKind::MultiColonyAco => MapfSolverKind::Cbs,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapfSolverKind::Cbs => "CBS",
+            MapfSolverKind::MultiColonyAco => "Multi-colony ACO",
+        }
+    }
+}
+
+/// Inputs a MAPF solution was computed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapfKey {
+    pub agents: Vec<AgentGoal>,
+    pub grid_revision: u64,
+    pub solver: MapfSolverKind,
+}
+
+#[derive(Resource)]
+pub struct MapfState {
+    pub solver: MapfSolverKind,
+    pub solution: Option<MapfSolution>,
+    pub solved_key: Option<MapfKey>,
+    pub playback: f32,
+    pub steps_per_second: f32,
+}
+
+impl Default for MapfState {
+    fn default() -> Self {
+        Self {
+            solver: MapfSolverKind::Cbs,
+            solution: None,
+            solved_key: None,
+            playback: 0.0,
+            steps_per_second: 3.0,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MapfTask {
+    pub task: Option<Task<Option<MapfSolution>>>,
+    pub key: Option<MapfKey>,
+    pub cancel_flag: Arc<AtomicBool>,
+}
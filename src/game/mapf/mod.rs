@@ -0,0 +1,18 @@
+mod component;
+mod system;
+
+use crate::game::control::GameState;
+use bevy::prelude::*;
+use component::{MapfState, MapfTask};
+use system::{handle_mapf_input, render_mapf, update_mapf};
+
+pub struct MapfPlugin;
+
+impl Plugin for MapfPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapfState>()
+            .init_resource::<MapfTask>()
+            .add_systems(Update, handle_mapf_input.run_if(in_state(GameState::Idle)))
+            .add_systems(Update, (update_mapf, render_mapf).chain());
+    }
+}
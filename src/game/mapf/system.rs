@@ -0,0 +1,145 @@
+use super::component::{MapfKey, MapfSolverKind, MapfState, MapfTask};
+use crate::algorithm::mapf::{cbs::CbsSolver, multi_colony::MultiColonyAco};
+use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::pathfinding_system::PathfindingStrategy;
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, block_on, poll_once};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub fn handle_mapf_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut algorithm_resource: ResMut<AlgorithmResource>,
+    mut mapf_state: ResMut<MapfState>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Digit4) {
+        algorithm_resource.problem.agents.clear();
+        println!("MAPF agents cleared");
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit5) {
+        mapf_state.solver = mapf_state.solver.next();
+        println!("MAPF solver: {}", mapf_state.solver.name());
+    }
+}
+
+/// Solves again on a worker whenever the agents, the walls or the solver change.
+pub fn update_mapf(
+    algorithm_resource: Res<AlgorithmResource>,
+    strategy_resource: Res<PathfindingStrategy>,
+    mut mapf_state: ResMut<MapfState>,
+    mut mapf_task: ResMut<MapfTask>,
+) {
+    let key = MapfKey {
+        agents: algorithm_resource.problem.agents.clone(),
+        grid_revision: algorithm_resource.grid.lock().unwrap().revision(),
+        solver: mapf_state.solver,
+    };
+
+    if mapf_task.task.is_some() && mapf_task.key.as_ref() != Some(&key) {
+        mapf_task.cancel_flag.store(true, Ordering::Relaxed);
+        mapf_task.task = None;
+        mapf_task.key = None;
+    }
+
+    if let Some(task) = mapf_task.task.as_mut() {
+        let Some(solution) = block_on(poll_once(task)) else {
+            return;
+        };
+        mapf_task.task = None;
+        let solved_key = mapf_task.key.take();
+
+        match &solution {
+            Some(x) => println!(
+                "MAPF ({}): {} agents ; sum of costs: {} ; makespan: {} ; conflicts: {} ; expanded: {}",
+                mapf_state.solver.name(),
+                x.paths.len(),
+                x.sum_of_costs,
+                x.makespan,
+                x.conflicts,
+                x.expanded
+            ),
+            None => println!("MAPF ({}): no solution", mapf_state.solver.name()),
+        }
+        mapf_state.solution = solution;
+        mapf_state.solved_key = solved_key;
+        mapf_state.playback = 0.0;
+        return;
+    }
+
+    if mapf_state.solved_key.as_ref() == Some(&key) {
+        return;
+    }
+    if key.agents.is_empty() {
+        if mapf_state.solution.is_some() {
+            mapf_state.solution = None;
+        }
+        mapf_state.solved_key = Some(key);
+        return;
+    }
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let grid = algorithm_resource.grid.clone();
+    let agents = key.agents.clone();
+    let solver = key.solver;
+    let flag = cancel_flag.clone();
+    // Seeded presets make the colony runs repeatable too.
+    let seed = strategy_resource.hybrid_strategy.seed();
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        match solver {
+            MapfSolverKind::Cbs => {
+                let mut cbs = CbsSolver::new(grid);
+                cbs.set_cancel_flag(Some(flag));
+                cbs.solve(&agents)
+            }
+            MapfSolverKind::MultiColonyAco => {
+                let mut aco = MultiColonyAco::new(grid);
+                aco.set_seed(seed);
+                aco.set_cancel_flag(Some(flag));
+                aco.solve(&agents)
+            }
+        }
+    });
+
+    mapf_task.task = Some(task);
+    mapf_task.key = Some(key);
+    mapf_task.cancel_flag = cancel_flag;
+}
+
+fn agent_color(index: usize) -> Color {
+    Color::hsl((index as f32 * 137.5) % 360.0, 0.85, 0.6)
+}
+
+/// Draws every agent's endpoints and plan, and replays the timed plans as moving dots.
+pub fn render_mapf(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut mapf_state: ResMut<MapfState>,
+) {
+    let cell_size = algorithm_resource.grid.lock().unwrap().cell_size();
+
+    for (i, agent) in algorithm_resource.problem.agents.iter().enumerate() {
+        let color = agent_color(i);
+        gizmos.circle_2d(Isometry2d::from_translation(agent.start), cell_size * 0.3, color);
+        gizmos.rect_2d(Isometry2d::from_translation(agent.goal), Vec2::splat(cell_size * 0.6), color);
+    }
+
+    let Some(solution) = mapf_state.solution.as_ref() else {
+        return;
+    };
+    // Hold the final positions for a moment before looping.
+    let duration = solution.makespan as f32 + 2.0;
+    let step = (mapf_state.playback + time.delta_secs() * mapf_state.steps_per_second) % duration;
+
+    for (i, path) in solution.paths.iter().enumerate() {
+        let color = agent_color(i);
+        gizmos.linestrip_2d(path.iter().copied(), color.with_alpha(0.6));
+
+        let index = (step as usize).min(path.len() - 1);
+        let next = (index + 1).min(path.len() - 1);
+        let pos = path[index].lerp(path[next], step.fract());
+        gizmos.circle_2d(Isometry2d::from_translation(pos), cell_size * 0.2, color);
+    }
+    mapf_state.playback = step;
+}
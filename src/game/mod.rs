@@ -1,12 +1,19 @@
+mod agent;
 mod algorithm_resource;
+mod ant_renderer;
 mod control;
+mod chart;
 mod grid_renderer;
+mod mapf;
+mod obstacle;
 mod solve_renderer;
+mod param_panel;
 mod plugins;
 mod system;
 mod pathfinding_system;
 mod click_position;
 mod timer;
 mod debug_system;
+mod config_system;
 
 pub mod prelude;
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// A box that drifts through the scene and turns the cells it covers into walls.
+#[derive(Component)]
+pub struct MovingObstacle {
+    pub velocity: Vec2,
+    pub size: Vec2,
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Grid cells currently walled off by obstacles, with how many obstacles cover each.
+#[derive(Resource, Default)]
+pub struct ObstacleCells {
+    pub stamped: HashMap<(usize, usize), u32>,
+}
+
+#[derive(Resource, Default)]
+pub struct ObstacleMetrics {
+    pub replans: u64,
+    pub hybrid_collisions: u64,
+    pub astar_collisions: u64,
+    pub compare_collisions: u64,
+    pub pending_replan: bool,
+    pub blocked_plan: [Option<u64>; 3],
+}
@@ -0,0 +1,23 @@
+mod component;
+mod system;
+
+pub use component::ObstacleCells;
+
+use component::ObstacleMetrics;
+
+use crate::game::control::GameState;
+use crate::game::pathfinding_system::update_pathfinding;
+use bevy::prelude::*;
+use system::{count_replans, detect_path_collisions, handle_obstacle_input, log_obstacle_metrics, move_obstacles};
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObstacleCells>()
+            .init_resource::<ObstacleMetrics>()
+            .add_systems(Update, handle_obstacle_input.run_if(in_state(GameState::Idle)))
+            .add_systems(FixedUpdate, (move_obstacles, detect_path_collisions).chain())
+            .add_systems(Update, (count_replans, log_obstacle_metrics).chain().after(update_pathfinding));
+    }
+}
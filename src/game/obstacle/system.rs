@@ -0,0 +1,251 @@
+use super::component::{MovingObstacle, ObstacleCells, ObstacleMetrics};
+use crate::algorithm::grid::{Grid, GridCell};
+use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::pathfinding_system::PlanningTask;
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+const OBSTACLE_SPEED: f32 = 90.0;
+
+pub fn handle_obstacle_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut obstacle_cells: ResMut<ObstacleCells>,
+    obstacle_query: Query<Entity, With<MovingObstacle>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        let mut grid = algorithm_resource.grid.lock().unwrap();
+        for ((x, y), _) in obstacle_cells.stamped.drain() {
+            grid.set(x, y, GridCell::Air);
+        }
+        for entity in obstacle_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        println!("Moving obstacles cleared");
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        let grid = algorithm_resource.grid.lock().unwrap();
+        let endpoints = endpoint_cells(&grid, &algorithm_resource);
+        let mut rng = rand::rng();
+
+        let free_cell = (0..50)
+            .map(|_| (rng.random_range(0..grid.width()), rng.random_range(0..grid.height())))
+            .find(|cell| {
+                grid.is_air(cell.0, cell.1) == Some(true)
+                    && !endpoints.contains(cell)
+                    && !obstacle_cells.stamped.contains_key(cell)
+            });
+        let Some((x, y)) = free_cell else {
+            println!("No free cell for a moving obstacle");
+            return;
+        };
+
+        let quad = grid.get_cell_quad(x, y).unwrap();
+        let center = quad.pos + quad.siz / 2.0;
+        let size = quad.siz * 0.8;
+        let velocity = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)) * OBSTACLE_SPEED;
+        let cells = covered_cells(&grid, center, size).unwrap_or_default();
+        drop(grid);
+
+        let mut grid = algorithm_resource.grid.lock().unwrap();
+        for &cell in cells.iter() {
+            stamp(&mut grid, &mut obstacle_cells.stamped, cell);
+        }
+
+        commands.spawn((
+            MovingObstacle { velocity, size, cells },
+            Sprite {
+                color: Color::srgb(0.8, 0.2, 0.7),
+                custom_size: Some(size),
+                ..default()
+            },
+            Transform::from_translation(center.extend(0.3)),
+        ));
+        println!("Moving obstacle spawned at grid: ({}, {})", x, y);
+    }
+}
+
+/// Moves every obstacle one fixed tick, bouncing off static walls, the grid border and the start/goal cells.
+pub fn move_obstacles(
+    time: Res<Time>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut obstacle_cells: ResMut<ObstacleCells>,
+    mut metrics: ResMut<ObstacleMetrics>,
+    mut obstacle_query: Query<(&mut Transform, &mut MovingObstacle)>,
+) {
+    if obstacle_query.is_empty() {
+        return;
+    }
+
+    let dt = time.delta_secs();
+    let mut grid = algorithm_resource.grid.lock().unwrap();
+    let endpoints = endpoint_cells(&grid, &algorithm_resource);
+    let revision = grid.revision();
+
+    for (mut transform, mut obstacle) in obstacle_query.iter_mut() {
+        let pos = transform.translation.truncate();
+        let blocked = |center: Vec2| match covered_cells(&grid, center, obstacle.size) {
+            Some(cells) => cells.iter().any(|cell| {
+                endpoints.contains(cell)
+                    || (grid.is_wall(cell.0, cell.1) == Some(true)
+                        && !obstacle_cells.stamped.contains_key(cell))
+            }),
+            None => true,
+        };
+
+        let mut next = pos;
+        let mut velocity = obstacle.velocity;
+        next.x += velocity.x * dt;
+        if blocked(next) {
+            next.x = pos.x;
+            velocity.x = -velocity.x;
+        }
+        next.y += velocity.y * dt;
+        if blocked(next) {
+            next.y = pos.y;
+            velocity.y = -velocity.y;
+        }
+        obstacle.velocity = velocity;
+        transform.translation = next.extend(transform.translation.z);
+
+        let cells = covered_cells(&grid, next, obstacle.size).unwrap_or_default();
+        if cells == obstacle.cells {
+            continue;
+        }
+        // Stamp before clearing so a shared cell never flickers to air.
+        for &cell in cells.iter().filter(|x| !obstacle.cells.contains(x)) {
+            stamp(&mut grid, &mut obstacle_cells.stamped, cell);
+        }
+        for &cell in obstacle.cells.iter().filter(|x| !cells.contains(x)) {
+            unstamp(&mut grid, &mut obstacle_cells.stamped, cell);
+        }
+        obstacle.cells = cells;
+    }
+
+    if grid.revision() != revision && !metrics.pending_replan {
+        metrics.pending_replan = true;
+    }
+}
+
+/// Counts each finished path once when a moving obstacle lands on it.
+pub fn detect_path_collisions(
+    algorithm_resource: Res<AlgorithmResource>,
+    planning_task: Res<PlanningTask>,
+    mut metrics: ResMut<ObstacleMetrics>,
+    obstacle_query: Query<(), With<MovingObstacle>>,
+) {
+    if obstacle_query.is_empty() {
+        return;
+    }
+
+    let plan = planning_task.completed;
+    let radius = algorithm_resource.problem.agent_radius;
+    let grid = algorithm_resource.grid.lock().unwrap();
+    let paths = [
+        &algorithm_resource.path,
+        &algorithm_resource.astar_path,
+        &algorithm_resource.compare_path,
+    ];
+
+    for (i, path) in paths.into_iter().enumerate() {
+        let Some(path) = path else {
+            continue;
+        };
+        if metrics.blocked_plan[i] == Some(plan) {
+            continue;
+        }
+        if path.windows(2).all(|x| grid.is_segment_clear(x[0], x[1], radius)) {
+            continue;
+        }
+
+        metrics.blocked_plan[i] = Some(plan);
+        match i {
+            0 => metrics.hybrid_collisions += 1,
+            1 => metrics.astar_collisions += 1,
+            _ => metrics.compare_collisions += 1,
+        }
+    }
+}
+
+pub fn count_replans(
+    planning_task: Res<PlanningTask>,
+    mut metrics: ResMut<ObstacleMetrics>,
+    mut last_completed: Local<u64>,
+) {
+    if planning_task.completed == *last_completed {
+        return;
+    }
+    *last_completed = planning_task.completed;
+
+    if metrics.pending_replan {
+        metrics.pending_replan = false;
+        metrics.replans += 1;
+    }
+}
+
+pub fn log_obstacle_metrics(
+    metrics: Res<ObstacleMetrics>,
+    planning_task: Res<PlanningTask>,
+    obstacle_query: Query<(), With<MovingObstacle>>,
+) {
+    if !metrics.is_changed() || metrics.is_added() {
+        return;
+    }
+    println!(
+        "Obstacles: {} ; replans: {} ; cancelled plans: {} ; collisions hybrid: {} ; astar: {} ; compare: {}",
+        obstacle_query.iter().count(),
+        metrics.replans,
+        planning_task.cancelled,
+        metrics.hybrid_collisions,
+        metrics.astar_collisions,
+        metrics.compare_collisions
+    );
+}
+
+/// Cells obstacles must keep clear: start, goal, waypoints and every MAPF agent's start and goal.
+fn endpoint_cells(grid: &Grid, algorithm_resource: &AlgorithmResource) -> Vec<(usize, usize)> {
+    let problem = &algorithm_resource.problem;
+    [problem.start, problem.goal]
+        .into_iter()
+        .flatten()
+        .chain(problem.waypoints.iter().copied())
+        .chain(problem.agents.iter().flat_map(|x| [x.start, x.goal]))
+        .filter_map(|x| grid.get_from_world_pos(x))
+        .collect()
+}
+
+/// Cells overlapped by a box, or `None` when the box leaves the grid.
+fn covered_cells(grid: &Grid, center: Vec2, size: Vec2) -> Option<Vec<(usize, usize)>> {
+    let min = center - size / 2.0 - grid.root();
+    let max = center + size / 2.0 - grid.root();
+    if min.x < 0.0 || min.y < 0.0 || max.x >= grid.world_width() || max.y >= grid.world_height() {
+        return None;
+    }
+
+    let cell_size = grid.cell_size();
+    let (x0, y0) = ((min.x / cell_size) as usize, (min.y / cell_size) as usize);
+    let (x1, y1) = ((max.x / cell_size) as usize, (max.y / cell_size) as usize);
+    Some((y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y))).collect())
+}
+
+fn stamp(grid: &mut Grid, stamped: &mut HashMap<(usize, usize), u32>, cell: (usize, usize)) {
+    let count = stamped.entry(cell).or_insert(0);
+    if *count == 0 {
+        grid.set(cell.0, cell.1, GridCell::Wall);
+    }
+    *count += 1;
+}
+
+fn unstamp(grid: &mut Grid, stamped: &mut HashMap<(usize, usize), u32>, cell: (usize, usize)) {
+    let Some(count) = stamped.get_mut(&cell) else {
+        return;
+    };
+    *count -= 1;
+    if *count == 0 {
+        stamped.remove(&cell);
+        grid.set(cell.0, cell.1, GridCell::Air);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::algorithm::solve::hybrid::HybridParam;
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct ParamPanel;
+
+#[derive(Component)]
+pub struct ParamLabel {
+    pub param: HybridParam,
+}
+
+#[derive(Component)]
+pub struct ParamSlider {
+    pub param: HybridParam,
+}
+
+#[derive(Component)]
+pub struct ParamSliderFill {
+    pub param: HybridParam,
+}
+
+#[derive(Resource, Default)]
+pub struct ParamPanelState {
+    pub visible: bool,
+}
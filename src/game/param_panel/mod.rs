@@ -0,0 +1,20 @@
+mod component;
+mod system;
+
+pub use component::ParamPanelState;
+
+use bevy::prelude::*;
+use system::{handle_param_sliders, setup_param_panel, toggle_param_panel, update_param_panel};
+
+pub struct ParamPanelPlugin;
+
+impl Plugin for ParamPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParamPanelState>()
+            .add_systems(Startup, setup_param_panel)
+            .add_systems(
+                Update,
+                (toggle_param_panel, handle_param_sliders, update_param_panel).chain(),
+            );
+    }
+}
@@ -0,0 +1,161 @@
+use super::component::{ParamLabel, ParamPanel, ParamPanelState, ParamSlider, ParamSliderFill};
+use crate::algorithm::solve::hybrid::HybridParam;
+use crate::game::pathfinding_system::PathfindingStrategy;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const TRACK_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const FILL_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+
+pub fn setup_param_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            ParamPanel,
+            Interaction::default(),
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("Hybrid parameters [Tab]"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+
+            for param in HybridParam::iter() {
+                panel
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            ParamLabel { param: *param },
+                            Text::new(""),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                            TextColor(TEXT_COLOR),
+                            Node {
+                                width: Val::Px(260.0),
+                                ..default()
+                            },
+                        ));
+                        row.spawn((
+                            ParamSlider { param: *param },
+                            Button,
+                            RelativeCursorPosition::default(),
+                            Node {
+                                width: Val::Px(150.0),
+                                height: Val::Px(12.0),
+                                ..default()
+                            },
+                            BackgroundColor(TRACK_COLOR),
+                        ))
+                        .with_children(|track| {
+                            track.spawn((
+                                ParamSliderFill { param: *param },
+                                Node {
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(FILL_COLOR),
+                            ));
+                        });
+                    });
+            }
+        });
+}
+
+pub fn toggle_param_panel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel_state: ResMut<ParamPanelState>,
+    mut panel_query: Query<&mut Node, With<ParamPanel>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    panel_state.visible = !panel_state.visible;
+    for mut node in panel_query.iter_mut() {
+        node.display = if panel_state.visible {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+pub fn handle_param_sliders(
+    panel_state: Res<ParamPanelState>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+    slider_query: Query<(&ParamSlider, &Interaction, &RelativeCursorPosition)>,
+) {
+    if !panel_state.visible {
+        return;
+    }
+
+    for (slider, interaction, cursor) in slider_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(normalized) = cursor.normalized else {
+            continue;
+        };
+
+        // The cursor position is centered on the node, so shift it into 0..1 along the track.
+        let t = (normalized.x + 0.5).clamp(0.0, 1.0) as f64;
+        let (min, max) = slider.param.range();
+        let hybrid = &mut strategy_resource.hybrid_strategy;
+        let before = hybrid.get_param(slider.param);
+        let value = hybrid.set_param(slider.param, min + t * (max - min));
+
+        if value != before && slider.param.invalidates_state() {
+            println!("{} changed, hybrid reset", slider.param.name());
+        }
+    }
+}
+
+pub fn update_param_panel(
+    panel_state: Res<ParamPanelState>,
+    strategy_resource: Res<PathfindingStrategy>,
+    mut label_query: Query<(&ParamLabel, &mut Text)>,
+    mut fill_query: Query<(&ParamSliderFill, &mut Node)>,
+) {
+    if !panel_state.visible {
+        return;
+    }
+
+    let hybrid = &strategy_resource.hybrid_strategy;
+
+    for (label, mut text) in label_query.iter_mut() {
+        let value = hybrid.get_param(label.param);
+        text.0 = if label.param.is_integer() {
+            format!("{}: {}", label.param.name(), value)
+        } else {
+            format!("{}: {:.3}", label.param.name(), value)
+        };
+    }
+
+    for (fill, mut node) in fill_query.iter_mut() {
+        let (min, max) = fill.param.range();
+        let t = (hybrid.get_param(fill.param) - min) / (max - min);
+        node.width = Val::Percent(100.0 * t.clamp(0.0, 1.0) as f32);
+    }
+}
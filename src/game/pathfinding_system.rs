@@ -1,12 +1,222 @@
-use crate::algorithm::solve::{hybrid::HybridStrategy, a_star::AStarStrategy};
+use crate::algorithm::clearance::ClearanceCost;
+use crate::algorithm::grid::Grid;
+use crate::algorithm::objective::ObjectiveWeights;
+use crate::algorithm::post_process::{PostProcessReport, PostProcessor};
+use crate::algorithm::problem::Problem;
+use crate::algorithm::solve::{hybrid::{HybridParam, HybridStrategy, PheromonePolicy}, a_star::AStarStrategy, d_star_lite::DStarLiteStrategy, genetic::GeneticStrategy, hpa::HpaStrategy, prm::PrmStrategy, rrt::{RrtStrategy, RrtVariant}, visibility::VisibilityGraphStrategy};
+use crate::algorithm::waypoints::{TourAco, WaypointOrder, order_stops, stop_regions};
+use crate::game::control::{RunControl, RunMode};
 use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Optional third solver planned next to the hybrid and A*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonSolver {
+    Off,
+    DStarLite,
+    Rrt,
+    RrtConnect,
+    RrtStar,
+    Prm,
+    VisibilityGraph,
+    Genetic,
+    Hpa,
+}
+
+impl ComparisonSolver {
+    pub fn next(&self) -> Self {
+        match self {
+            ComparisonSolver::Off => ComparisonSolver::DStarLite,
+            ComparisonSolver::DStarLite => ComparisonSolver::Rrt,
+            ComparisonSolver::Rrt => ComparisonSolver::RrtConnect,
+            ComparisonSolver::RrtConnect => ComparisonSolver::RrtStar,
+            ComparisonSolver::RrtStar => ComparisonSolver::Prm,
+            ComparisonSolver::Prm => ComparisonSolver::VisibilityGraph,
+            ComparisonSolver::VisibilityGraph => ComparisonSolver::Genetic,
+            ComparisonSolver::Genetic => ComparisonSolver::Hpa,
+            ComparisonSolver::Hpa => ComparisonSolver::Off,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComparisonSolver::Off => "Off",
+            ComparisonSolver::DStarLite => "D* Lite",
+            ComparisonSolver::Rrt => "RRT",
+            ComparisonSolver::RrtConnect => "RRT-Connect",
+            ComparisonSolver::RrtStar => "RRT*",
+            ComparisonSolver::Prm => "PRM",
+            ComparisonSolver::VisibilityGraph => "Visibility graph",
+            ComparisonSolver::Genetic => "GA",
+            ComparisonSolver::Hpa => "HPA*",
+        }
+    }
+}
+
+/// State of every comparison solver. Only the selected one travels with the planning task, see
+/// `ComparisonRun`.
+pub struct ComparisonStrategies {
+    pub dstar: DStarLiteStrategy,
+    pub rrt: RrtStrategy,
+    pub prm: PrmStrategy,
+    pub visibility: VisibilityGraphStrategy,
+    pub genetic: GeneticStrategy,
+    pub hpa: HpaStrategy,
+    /// One GA per leg after the first, so every leg's population keeps evolving across plans.
+    genetic_legs: Vec<GeneticStrategy>,
+}
+
+impl ComparisonStrategies {
+    pub fn new(grid: Arc<Mutex<Grid>>) -> Self {
+        Self {
+            dstar: DStarLiteStrategy::new(grid.clone()),
+            rrt: RrtStrategy::new(grid.clone()),
+            prm: PrmStrategy::new(grid.clone()),
+            visibility: VisibilityGraphStrategy::new(grid.clone()),
+            genetic: GeneticStrategy::new(grid.clone()),
+            hpa: HpaStrategy::new(grid),
+            genetic_legs: Vec::new(),
+        }
+    }
+
+    /// Hands the settings of the next plan to every solver, and one GA to each of `legs` legs.
+    fn configure(&mut self, agent_radius: f32, seed: Option<u64>, legs: usize) {
+        self.dstar.agent_radius = agent_radius;
+        self.rrt.agent_radius = agent_radius;
+        self.rrt.set_seed(seed);
+        self.prm.agent_radius = agent_radius;
+        self.prm.set_seed(seed);
+        self.visibility.agent_radius = agent_radius;
+        self.hpa.agent_radius = agent_radius;
+        self.genetic.agent_radius = agent_radius;
+        self.genetic.set_seed(seed);
+        self.sync_genetic_legs(legs.saturating_sub(1));
+    }
+
+    fn sync_genetic_legs(&mut self, count: usize) {
+        self.genetic_legs.truncate(count);
+        while self.genetic_legs.len() < count {
+            let mut genetic = self.genetic.clone();
+            genetic.reset();
+            self.genetic_legs.push(genetic);
+        }
+        for leg in self.genetic_legs.iter_mut() {
+            leg.match_settings(&self.genetic);
+        }
+    }
+
+    pub fn reset_genetic(&mut self) {
+        self.genetic.reset();
+        self.genetic_legs.clear();
+    }
+
+    /// A copy of the selected solver for the planning task, leaving the others in place.
+    fn checkout(&self, comparison: ComparisonSolver) -> ComparisonRun {
+        let rrt_variant = match comparison {
+            ComparisonSolver::Off => return ComparisonRun::Off,
+            ComparisonSolver::DStarLite => return ComparisonRun::DStarLite(self.dstar.clone()),
+            ComparisonSolver::Prm => return ComparisonRun::Prm(self.prm.clone()),
+            ComparisonSolver::VisibilityGraph => return ComparisonRun::VisibilityGraph(self.visibility.clone()),
+            ComparisonSolver::Genetic => {
+                let genetic = std::iter::once(&self.genetic).chain(self.genetic_legs.iter());
+                return ComparisonRun::Genetic(genetic.cloned().collect());
+            }
+            ComparisonSolver::Hpa => return ComparisonRun::Hpa(self.hpa.clone()),
+            ComparisonSolver::Rrt => RrtVariant::Rrt,
+            ComparisonSolver::RrtConnect => RrtVariant::Connect,
+            ComparisonSolver::RrtStar => RrtVariant::Star,
+        };
+        let mut rrt = self.rrt.clone();
+        rrt.variant = rrt_variant;
+        ComparisonRun::Rrt(rrt)
+    }
+
+    /// Takes back the solver a finished plan ran.
+    fn checkin(&mut self, run: ComparisonRun) {
+        match run {
+            ComparisonRun::Off => {}
+            ComparisonRun::DStarLite(x) => self.dstar = x,
+            ComparisonRun::Rrt(x) => self.rrt = x,
+            ComparisonRun::Prm(x) => self.prm = x,
+            ComparisonRun::VisibilityGraph(x) => self.visibility = x,
+            ComparisonRun::Genetic(mut x) => {
+                self.genetic_legs = x.split_off(1);
+                self.genetic = x.remove(0);
+            }
+            ComparisonRun::Hpa(x) => self.hpa = x,
+        }
+    }
+}
+
+/// The one comparison solver a plan runs, so the others are not copied for every plan.
+enum ComparisonRun {
+    Off,
+    DStarLite(DStarLiteStrategy),
+    Rrt(RrtStrategy),
+    Prm(PrmStrategy),
+    VisibilityGraph(VisibilityGraphStrategy),
+    /// One GA per leg, the first being `ComparisonStrategies::genetic`.
+    Genetic(Vec<GeneticStrategy>),
+    Hpa(HpaStrategy),
+}
+
+impl ComparisonRun {
+    fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        match self {
+            ComparisonRun::Off => {}
+            ComparisonRun::DStarLite(x) => x.set_cancel_flag(flag),
+            ComparisonRun::Rrt(x) => x.set_cancel_flag(flag),
+            ComparisonRun::Prm(x) => x.set_cancel_flag(flag),
+            ComparisonRun::VisibilityGraph(x) => x.set_cancel_flag(flag),
+            ComparisonRun::Genetic(x) => {
+                for genetic in x.iter_mut() {
+                    genetic.set_cancel_flag(flag.clone());
+                }
+            }
+            ComparisonRun::Hpa(x) => x.set_cancel_flag(flag),
+        }
+    }
+
+    /// Plans leg `leg` of the route.
+    fn plan(&mut self, leg: usize, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let (start, goal) = (Some(start), Some(goal));
+        match self {
+            ComparisonRun::Off => None,
+            ComparisonRun::DStarLite(x) => x.path_finding(start, goal),
+            ComparisonRun::Rrt(x) => x.path_finding(start, goal),
+            ComparisonRun::Prm(x) => x.path_finding(start, goal),
+            ComparisonRun::VisibilityGraph(x) => x.path_finding(start, goal),
+            ComparisonRun::Genetic(x) => x[leg].path_finding(start, goal),
+            ComparisonRun::Hpa(x) => x.path_finding(start, goal),
+        }
+    }
+}
+
+/// Inputs the visiting order of the waypoints was solved from. Wall edits only call for a new
+/// order when they change which stops can reach each other.
+#[derive(Debug, Clone, PartialEq)]
+struct RouteKey {
+    start: Vec2,
+    goal: Vec2,
+    waypoints: Vec<Vec2>,
+    order: WaypointOrder,
+    regions: Vec<Option<usize>>,
+    seed: Option<u64>,
+}
 
 #[derive(Resource)]
 pub struct PathfindingStrategy {
     pub hybrid_strategy: HybridStrategy,
     pub astar_strategy: AStarStrategy,
+    pub comparison_strategies: ComparisonStrategies,
+    pub comparison: ComparisonSolver,
+    pub post_processor: PostProcessor,
+    pub tour: TourAco,
+    hybrid_legs: Vec<HybridStrategy>,
+    route: Option<(RouteKey, Vec<Vec2>)>,
 }
 
 impl PathfindingStrategy {
@@ -14,48 +224,491 @@ impl PathfindingStrategy {
         Self {
             hybrid_strategy: HybridStrategy::new(grid.clone()),
             astar_strategy: AStarStrategy::new(grid.clone()),
+            comparison_strategies: ComparisonStrategies::new(grid.clone()),
+            comparison: ComparisonSolver::Off,
+            post_processor: PostProcessor::default(),
+            tour: TourAco::new(),
+            hybrid_legs: Vec::new(),
+            route: None,
+        }
+    }
+
+    /// Inputs of the next route, with its stops when they are known without solving a tour.
+    /// Any-order tours are solved by the planning task and cached until their inputs change.
+    fn route(&self, problem: &Problem) -> Option<(RouteKey, Option<Vec<Vec2>>)> {
+        let (start, goal) = (problem.start?, problem.goal?);
+        let order = problem.waypoint_order;
+        let regions = if order == WaypointOrder::AnyOrder && problem.waypoints.len() > 1 {
+            let stops: Vec<Vec2> = std::iter::once(start)
+                .chain(problem.waypoints.iter().copied())
+                .chain(std::iter::once(goal))
+                .collect();
+            stop_regions(&problem.grid.lock().unwrap(), &stops)
+        } else {
+            Vec::new()
+        };
+        let key = RouteKey {
+            start,
+            goal,
+            waypoints: problem.waypoints.clone(),
+            order,
+            regions,
+            seed: self.hybrid_strategy.seed(),
+        };
+        if key.order == WaypointOrder::InOrder || key.waypoints.len() < 2 {
+            let stops = std::iter::once(key.start)
+                .chain(key.waypoints.iter().copied())
+                .chain(std::iter::once(key.goal))
+                .collect();
+            return Some((key, Some(stops)));
+        }
+
+        let stops = self.route.as_ref().filter(|x| x.0 == key).map(|x| x.1.clone());
+        Some((key, stops))
+    }
+
+    /// Last solved stop sequence, for drawing.
+    pub fn cached_route(&self) -> Option<&[Vec2]> {
+        self.route.as_ref().map(|x| x.1.as_slice())
+    }
+
+    /// One hybrid per leg after the first, rebuilt from the main hybrid whenever its settings differ.
+    fn sync_hybrid_legs(&mut self, count: usize) {
+        let main = &self.hybrid_strategy;
+        let matches = |leg: &HybridStrategy| {
+            HybridParam::iter().all(|x| leg.get_param(*x) == main.get_param(*x))
+                && leg.seed() == main.seed()
+                && leg.clearance_cost == main.clearance_cost
+                && leg.geodesic_heuristic == main.geodesic_heuristic
+                && leg.objective == main.objective
+                && leg.pareto == main.pareto
+                && leg.warm_start == main.warm_start
+                && leg.pheromone_policy() == main.pheromone_policy()
+        };
+        self.hybrid_legs.truncate(count);
+        for leg in self.hybrid_legs.iter_mut() {
+            if !matches(leg) {
+                *leg = fresh_hybrid(main);
+            }
+        }
+        while self.hybrid_legs.len() < count {
+            self.hybrid_legs.push(fresh_hybrid(main));
         }
     }
+
+    pub fn reset_legs(&mut self) {
+        self.hybrid_legs.clear();
+    }
+
+    /// Points the hybrid at Pareto tour `choice` and returns it post-processed like a planned path.
+    /// Nothing is planned again. Pareto mode is off while there are waypoints, so there is one leg.
+    pub fn select_pareto_tour(
+        &mut self,
+        choice: usize,
+        grid: &Grid,
+        agent_radius: f32,
+    ) -> (Option<Vec<Vec2>>, Option<PostProcessReport>) {
+        self.hybrid_strategy.pareto_choice = choice;
+        let path = self.hybrid_strategy.best_path();
+        stitch_legs(vec![path], &self.post_processor, grid, agent_radius)
+    }
 }
 
+fn fresh_hybrid(template: &HybridStrategy) -> HybridStrategy {
+    let mut hybrid = template.clone();
+    hybrid.reset();
+    hybrid.reset_particles();
+    hybrid
+}
+
+/// Post-processes each leg on its own so waypoints stay on the path, then joins them.
+fn stitch_legs(
+    legs: Vec<Option<Vec<Vec2>>>,
+    post_processor: &PostProcessor,
+    grid: &Grid,
+    agent_radius: f32,
+) -> (Option<Vec<Vec2>>, Option<PostProcessReport>) {
+    let mut path: Vec<Vec2> = Vec::new();
+    let mut report: Option<PostProcessReport> = None;
+
+    for leg in legs {
+        let Some(leg) = leg else {
+            return (None, report);
+        };
+        let leg = if post_processor.is_identity() {
+            leg
+        } else {
+            let (leg, leg_report) = post_processor.process(grid, &leg, agent_radius);
+            report = Some(match report {
+                Some(x) => PostProcessReport {
+                    raw_len: x.raw_len + leg_report.raw_len,
+                    pruned_len: x.pruned_len + leg_report.pruned_len,
+                    final_len: x.final_len + leg_report.final_len,
+                    smoothing_rejected: x.smoothing_rejected || leg_report.smoothing_rejected,
+                },
+                None => leg_report,
+            });
+            leg
+        };
+        let skip = usize::from(path.last().is_some_and(|x| leg.first() == Some(x)));
+        path.extend(leg.into_iter().skip(skip));
+    }
+    (Some(path), report)
+}
+
+pub struct PlanningResult {
+    route: (RouteKey, Vec<Vec2>),
+    hybrid_strategy: HybridStrategy,
+    hybrid_legs: Vec<HybridStrategy>,
+    astar_strategy: AStarStrategy,
+    comparison: ComparisonRun,
+    hybrid_path: Option<Vec<Vec2>>,
+    astar_path: Option<Vec<Vec2>>,
+    compare_path: Option<Vec<Vec2>>,
+    hybrid_report: Option<PostProcessReport>,
+    astar_report: Option<PostProcessReport>,
+    compare_report: Option<PostProcessReport>,
+    hybrid_duration: Duration,
+    astar_duration: Duration,
+    compare_duration: Duration,
+}
+
+/// Everything a running plan was started from. When any of it changes the plan is stale.
+#[derive(Debug, Clone, PartialEq)]
+struct PlanningKey {
+    start: Vec2,
+    goal: Vec2,
+    grid_revision: u64,
+    hybrid_iteration: u64,
+    hybrid_params: Vec<f64>,
+    hybrid_seed: Option<u64>,
+    tour_history_size: usize,
+    step_size: f32,
+    record_search: bool,
+    agent_radius: f32,
+    astar_clearance: ClearanceCost,
+    hybrid_clearance: ClearanceCost,
+    geodesic_heuristic: bool,
+    objective: ObjectiveWeights,
+    pareto: bool,
+    warm_start: bool,
+    pheromone_policy: PheromonePolicy,
+    comparison: ComparisonSolver,
+    waypoints: Vec<Vec2>,
+    waypoint_order: WaypointOrder,
+    post_processor: PostProcessor,
+}
+
+impl PlanningKey {
+    fn new(
+        strategy: &PathfindingStrategy,
+        algorithm_resource: &crate::game::algorithm_resource::AlgorithmResource,
+    ) -> Option<Self> {
+        let hybrid = &strategy.hybrid_strategy;
+        Some(Self {
+            start: algorithm_resource.problem.start?,
+            goal: algorithm_resource.problem.goal?,
+            grid_revision: algorithm_resource.grid.lock().unwrap().revision(),
+            hybrid_iteration: hybrid.iteration(),
+            hybrid_params: HybridParam::iter().map(|x| hybrid.get_param(*x)).collect(),
+            hybrid_seed: hybrid.seed(),
+            tour_history_size: hybrid.tour_history_size,
+            step_size: strategy.astar_strategy.step_size,
+            record_search: strategy.astar_strategy.record_search,
+            agent_radius: algorithm_resource.problem.agent_radius,
+            astar_clearance: strategy.astar_strategy.clearance_cost,
+            hybrid_clearance: hybrid.clearance_cost,
+            geodesic_heuristic: hybrid.geodesic_heuristic,
+            objective: hybrid.objective,
+            pareto: hybrid.pareto,
+            warm_start: hybrid.warm_start,
+            pheromone_policy: hybrid.pheromone_policy(),
+            comparison: strategy.comparison,
+            waypoints: algorithm_resource.problem.waypoints.clone(),
+            waypoint_order: algorithm_resource.problem.waypoint_order,
+            post_processor: strategy.post_processor.clone(),
+        })
+    }
+
+    /// Equal apart from the grid revision, so moving obstacles let a running plan finish and the
+    /// next plan starts from the newer grid. User wall and terrain edits cancel the plan themselves.
+    fn same_problem(&self, other: &Self) -> bool {
+        Self {
+            grid_revision: other.grid_revision,
+            ..self.clone()
+        } == *other
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PlanningTask {
+    pub completed: u64,
+    pub cancelled: u64,
+    task: Option<Task<PlanningResult>>,
+    key: Option<PlanningKey>,
+    cancel_flag: Arc<AtomicBool>,
+    started: Option<Instant>,
+}
+
+impl PlanningTask {
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    pub fn running_for(&self) -> Option<Duration> {
+        self.started.filter(|_| self.is_running()).map(|x| x.elapsed())
+    }
+
+    pub fn cancel(&mut self) {
+        if self.task.take().is_some() {
+            self.cancel_flag.store(true, Ordering::Relaxed);
+            self.cancelled += 1;
+            println!("Planning cancelled");
+        }
+        self.key = None;
+        self.started = None;
+    }
+}
+
+#[derive(Component)]
+pub struct PlanningIndicator;
+
 pub fn update_pathfinding(
     mut strategy_resource: ResMut<PathfindingStrategy>,
     mut algorithm_resource: ResMut<crate::game::algorithm_resource::AlgorithmResource>,
     mut timers: ResMut<crate::game::timer::AlgorithmTimers>,
+    mut planning_task: ResMut<PlanningTask>,
+    run_mode: Res<State<RunMode>>,
+    run_control: Res<RunControl>,
 ) {
-    let start = algorithm_resource.problem.start;
-    let goal = algorithm_resource.problem.goal;
+    // Every waypoint leg has a front of its own, and one index does not pick matching tours.
+    if strategy_resource.hybrid_strategy.pareto && !algorithm_resource.problem.waypoints.is_empty() {
+        strategy_resource.hybrid_strategy.pareto = false;
+        println!("Pareto front off: it is not available with waypoints");
+    }
 
-    if let (Some(start), Some(goal)) = (start, goal) {
-        let hybrid_start_time = Instant::now();
-        let hybrid_path = strategy_resource
-            .hybrid_strategy
-            .path_finding(Some(start), Some(goal));
-        let hybrid_duration = hybrid_start_time.elapsed();
+    let key = PlanningKey::new(&strategy_resource, &algorithm_resource);
 
-        let astar_start_time = Instant::now();
-        let astar_path = strategy_resource
-            .astar_strategy
-            .path_finding(Some(start), Some(goal));
-        let astar_duration = astar_start_time.elapsed();
+    let stale = match (&planning_task.key, &key) {
+        (Some(running), Some(key)) => !running.same_problem(key),
+        (running, key) => running != key,
+    };
+    if planning_task.is_running() && stale {
+        planning_task.cancel();
+    }
+
+    if let Some(task) = planning_task.task.as_mut() {
+        let Some(result) = block_on(poll_once(task)) else {
+            return;
+        };
+        planning_task.task = None;
+        planning_task.started = None;
+        planning_task.completed += 1;
 
-        timers.hybrid_last_ms = hybrid_duration.as_secs_f64() * 1000.0;
+        // The shown Pareto tour may have been switched while the plan ran.
+        let pareto_choice = strategy_resource.hybrid_strategy.pareto_choice;
+        strategy_resource.route = Some(result.route);
+        strategy_resource.hybrid_strategy = result.hybrid_strategy;
+        strategy_resource.hybrid_legs = result.hybrid_legs;
+        strategy_resource.astar_strategy = result.astar_strategy;
+        strategy_resource.comparison_strategies.checkin(result.comparison);
+
+        timers.hybrid_last_ms = result.hybrid_duration.as_secs_f64() * 1000.0;
         timers.hybrid_total_ms += timers.hybrid_last_ms;
         if timers.hybrid_last_ms > timers.hybrid_max_ms {
             timers.hybrid_max_ms = timers.hybrid_last_ms;
         }
 
-        timers.a_star_last_ms = astar_duration.as_secs_f64() * 1000.0;
+        timers.a_star_last_ms = result.astar_duration.as_secs_f64() * 1000.0;
         timers.a_star_total_ms += timers.a_star_last_ms;
         if timers.a_star_last_ms > timers.a_star_max_ms {
             timers.a_star_max_ms = timers.a_star_last_ms;
         }
 
+        timers.compare_last_ms = result.compare_duration.as_secs_f64() * 1000.0;
+        timers.compare_total_ms += timers.compare_last_ms;
+        if timers.compare_last_ms > timers.compare_max_ms {
+            timers.compare_max_ms = timers.compare_last_ms;
+        }
+
+        let (hybrid_path, hybrid_report) = if strategy_resource.hybrid_strategy.pareto {
+            let grid = algorithm_resource.grid.clone();
+            let grid = grid.lock().unwrap();
+            let radius = algorithm_resource.problem.agent_radius;
+            strategy_resource.select_pareto_tour(pareto_choice, &grid, radius)
+        } else {
+            (result.hybrid_path, result.hybrid_report)
+        };
         if algorithm_resource.path != hybrid_path {
             algorithm_resource.path = hybrid_path;
         }
-        if algorithm_resource.astar_path != astar_path {
-            algorithm_resource.astar_path = astar_path;
+        if algorithm_resource.astar_path != result.astar_path {
+            algorithm_resource.astar_path = result.astar_path;
+        }
+        if algorithm_resource.compare_path != result.compare_path {
+            algorithm_resource.compare_path = result.compare_path;
+        }
+        algorithm_resource.path_report = hybrid_report;
+        algorithm_resource.astar_path_report = result.astar_report;
+        algorithm_resource.compare_path_report = result.compare_report;
+        return;
+    }
+
+    if !run_control.should_run(run_mode.get()) {
+        return;
+    }
+    let Some(key) = key else {
+        return;
+    };
+    let Some((route_key, known_stops)) = strategy_resource.route(&algorithm_resource.problem) else {
+        return;
+    };
+    strategy_resource.sync_hybrid_legs(route_key.waypoints.len());
+    let previous_stops = strategy_resource.cached_route().map(|x| x.to_vec());
+    let mut tour = strategy_resource.tour.clone();
+
+    // Solvers run on copies so the rest of the app keeps reading the last finished state.
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let mut hybrid_strategy = strategy_resource.hybrid_strategy.clone();
+    let mut astar_strategy = strategy_resource.astar_strategy.clone();
+    hybrid_strategy.agent_radius = key.agent_radius;
+    astar_strategy.agent_radius = key.agent_radius;
+    let mut hybrid_legs = strategy_resource.hybrid_legs.clone();
+    let comparison_strategies = &mut strategy_resource.comparison_strategies;
+    comparison_strategies.configure(key.agent_radius, key.hybrid_seed, route_key.waypoints.len() + 1);
+    let mut comparison = comparison_strategies.checkout(key.comparison);
+    let agent_radius = key.agent_radius;
+    let post_processor = strategy_resource.post_processor.clone();
+    let grid = algorithm_resource.grid.clone();
+    hybrid_strategy.set_cancel_flag(Some(cancel_flag.clone()));
+    for leg in hybrid_legs.iter_mut() {
+        leg.agent_radius = key.agent_radius;
+        leg.set_cancel_flag(Some(cancel_flag.clone()));
+    }
+    astar_strategy.set_cancel_flag(Some(cancel_flag.clone()));
+    comparison.set_cancel_flag(Some(cancel_flag.clone()));
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let stops = known_stops.unwrap_or_else(|| {
+            // Work on a copy so the tour's distance fields do not hold the grid lock.
+            let grid = grid.lock().unwrap().clone();
+            tour.set_seed(route_key.seed);
+            let stops = order_stops(
+                &grid,
+                route_key.start,
+                &route_key.waypoints,
+                route_key.goal,
+                route_key.order,
+                &tour,
+            );
+            println!("Waypoint tour solved: {} stops", stops.len());
+            stops
+        });
+        // Legs after the first now connect different stops, so their colonies start over.
+        if previous_stops.as_ref() != Some(&stops) {
+            for leg in hybrid_legs.iter_mut() {
+                *leg = fresh_hybrid(leg);
+            }
+        }
+
+        let legs = || stops.windows(2).map(|x| (x[0], x[1]));
+
+        let hybrid_start_time = Instant::now();
+        let mut hybrid_paths = Vec::new();
+        for (i, (from, to)) in legs().enumerate() {
+            let strategy = match i {
+                0 => &mut hybrid_strategy,
+                _ => &mut hybrid_legs[i - 1],
+            };
+            hybrid_paths.push(strategy.path_finding(Some(from), Some(to)));
+        }
+        let hybrid_duration = hybrid_start_time.elapsed();
+
+        let astar_start_time = Instant::now();
+        let astar_paths: Vec<_> = legs()
+            .map(|(from, to)| astar_strategy.path_finding(Some(from), Some(to)))
+            .collect();
+        let astar_duration = astar_start_time.elapsed();
+
+        let compare_start_time = Instant::now();
+        let compare_paths: Vec<_> = legs()
+            .enumerate()
+            .map(|(i, (from, to))| comparison.plan(i, from, to))
+            .collect();
+        let compare_duration = compare_start_time.elapsed();
+
+        hybrid_strategy.set_cancel_flag(None);
+        astar_strategy.set_cancel_flag(None);
+        comparison.set_cancel_flag(None);
+        for leg in hybrid_legs.iter_mut() {
+            leg.set_cancel_flag(None);
+        }
+
+        let grid = grid.lock().unwrap();
+        let (hybrid_path, hybrid_report) =
+            stitch_legs(hybrid_paths, &post_processor, &grid, agent_radius);
+        let (astar_path, astar_report) =
+            stitch_legs(astar_paths, &post_processor, &grid, agent_radius);
+        let (compare_path, compare_report) =
+            stitch_legs(compare_paths, &post_processor, &grid, agent_radius);
+        drop(grid);
+
+        PlanningResult {
+            route: (route_key, stops),
+            hybrid_strategy,
+            hybrid_legs,
+            astar_strategy,
+            comparison,
+            hybrid_path,
+            astar_path,
+            compare_path,
+            hybrid_report,
+            astar_report,
+            compare_report,
+            hybrid_duration,
+            astar_duration,
+            compare_duration,
+        }
+    });
+
+    planning_task.task = Some(task);
+    planning_task.key = Some(key);
+    planning_task.cancel_flag = cancel_flag;
+    planning_task.started = Some(Instant::now());
+}
+
+pub fn setup_planning_indicator(mut commands: Commands) {
+    commands.spawn((
+        PlanningIndicator,
+        Text::new("Planning..."),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.8, 0.2)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            display: Display::None,
+            ..default()
+        },
+    ));
+}
+
+pub fn update_planning_indicator(
+    planning_task: Res<PlanningTask>,
+    mut indicator_query: Query<&mut Node, With<PlanningIndicator>>,
+) {
+    // Plans that finish within a few frames would only make the label flicker.
+    let visible = planning_task
+        .running_for()
+        .is_some_and(|x| x > Duration::from_millis(150));
+
+    for mut node in indicator_query.iter_mut() {
+        let display = if visible { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
         }
     }
 }
@@ -66,5 +719,7 @@ pub fn reset_pathfinding(
     mut timers: ResMut<crate::game::timer::AlgorithmTimers>,
 ) {
     strategy_resource.hybrid_strategy.reset();
+    strategy_resource.reset_legs();
+    strategy_resource.comparison_strategies.reset_genetic();
     timers.reset_totals();
 }
@@ -1,10 +1,17 @@
+use crate::game::agent::AgentPlugin;
+use crate::game::ant_renderer::AntTrailPlugin;
+use crate::game::chart::ChartPlugin;
 use crate::game::control::{ControlPlugin, GameState};
 use crate::game::grid_renderer::GridPlugin;
+use crate::game::mapf::MapfPlugin;
+use crate::game::obstacle::ObstaclePlugin;
+use crate::game::param_panel::ParamPanelPlugin;
 use crate::game::solve_renderer::SolvePlugin;
 use crate::game::system::*;
-use crate::game::pathfinding_system::{PathfindingStrategy, update_pathfinding};
+use crate::game::pathfinding_system::{PathfindingStrategy, PlanningTask, setup_planning_indicator, update_pathfinding, update_planning_indicator};
 use crate::game::timer::AlgorithmTimers;
-use crate::game::debug_system::log_timing_info;
+use crate::game::debug_system::{log_timing_info, run_hpa_benchmark};
+use crate::game::config_system::{SolverConfigResource, handle_config_input, load_solver_config};
 use bevy::prelude::*;
 
 pub struct GameScenePlugin;
@@ -17,11 +24,22 @@ impl Plugin for GameScenePlugin {
         app.insert_resource(algorithm_resource)
             .insert_resource(pathfinding_strategy)
             .insert_resource(AlgorithmTimers::default())
-            .add_systems(Startup, setup_game)
+            .init_resource::<PlanningTask>()
+            .init_resource::<SolverConfigResource>()
+            .add_systems(Startup, (setup_game, load_solver_config, setup_planning_indicator))
+            .add_systems(Update, handle_config_input)
+            .add_systems(Update, run_hpa_benchmark)
             .add_systems(Update, (update_pathfinding, log_timing_info).chain())
+            .add_systems(Update, update_planning_indicator.after(update_pathfinding))
             .add_plugins(GridPlugin)
             .add_plugins(ControlPlugin)
             .add_plugins(SolvePlugin)
+            .add_plugins(ChartPlugin)
+            .add_plugins(AntTrailPlugin)
+            .add_plugins(ParamPanelPlugin)
+            .add_plugins(ObstaclePlugin)
+            .add_plugins(AgentPlugin)
+            .add_plugins(MapfPlugin)
             .insert_state(GameState::Idle);
     }
 }
@@ -1,3 +1,4 @@
+use crate::algorithm::flow_field::FlowField;
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -24,6 +25,48 @@ pub struct AStarPathRenderer {
     pub color: Color,
 }
 
+#[derive(Component)]
+pub struct ComparePathRenderer;
+
+#[derive(Component)]
+pub struct PheromoneRenderer;
+
+#[derive(Resource, Default)]
+pub struct PheromoneOverlay {
+    pub visible: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct DistanceFieldOverlay {
+    pub visible: bool,
+}
+
+/// Arrows towards the goal; the field is rebuilt when the goal or the walls change.
+#[derive(Resource, Default)]
+pub struct FlowFieldOverlay {
+    pub visible: bool,
+    pub field: Option<FlowField>,
+}
+
+#[derive(Resource)]
+pub struct SearchFrontierOverlay {
+    pub visible: bool,
+    pub color_by_g: bool,
+    pub progress: f32,
+    pub steps_per_second: f32,
+}
+
+impl Default for SearchFrontierOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            color_by_g: false,
+            progress: 0.0,
+            steps_per_second: 120.0,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct TemporaryLineRenderer {
     pub timer: Timer,
@@ -1,8 +1,8 @@
 mod component;
 mod system;
 
-pub use system::{render_start_goal, render_path, render_temporary_lines, temporary_line_render, temp_debug_line, clear_path};
-pub use component::{StartPoint, GoalPoint, PathRenderer, AStarPathRenderer, TemporaryLineRenderer, TemporaryLines};
+pub use system::{render_start_goal, render_agent_radius, render_waypoints, render_pareto_front, render_rrt_tree, render_roadmap, render_path, render_pheromones, toggle_pheromone_overlay, render_distance_field, toggle_distance_field_overlay, render_flow_field, toggle_flow_field_overlay, render_search_frontier, toggle_search_frontier, render_temporary_lines, temporary_line_render, temp_debug_line, clear_path};
+pub use component::{StartPoint, GoalPoint, PathRenderer, AStarPathRenderer, PheromoneOverlay, DistanceFieldOverlay, FlowFieldOverlay, SearchFrontierOverlay, TemporaryLineRenderer, TemporaryLines};
 
 use bevy::prelude::*;
 
@@ -11,8 +11,21 @@ pub struct SolvePlugin;
 impl Plugin for SolvePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TemporaryLines>()
+            .init_resource::<PheromoneOverlay>()
+            .init_resource::<SearchFrontierOverlay>()
+            .init_resource::<DistanceFieldOverlay>()
+            .init_resource::<FlowFieldOverlay>()
             .add_systems(Update, render_start_goal)
             .add_systems(Update, render_path)
+            .add_systems(Update, render_agent_radius)
+            .add_systems(Update, render_waypoints)
+            .add_systems(Update, render_pareto_front)
+            .add_systems(Update, render_rrt_tree)
+            .add_systems(Update, render_roadmap)
+            .add_systems(Update, (toggle_pheromone_overlay, render_pheromones).chain())
+            .add_systems(Update, (toggle_search_frontier, render_search_frontier).chain())
+            .add_systems(Update, (toggle_distance_field_overlay, render_distance_field).chain())
+            .add_systems(Update, (toggle_flow_field_overlay, render_flow_field).chain())
             .add_systems(Update, render_temporary_lines);
     }
 }
@@ -1,5 +1,8 @@
-use super::component::{GoalPoint, PathRenderer, AStarPathRenderer, PointRenderer, StartPoint, TemporaryLineRenderer, TemporaryLines};
+use super::component::{ComparePathRenderer, DistanceFieldOverlay, FlowFieldOverlay, GoalPoint, PathRenderer, AStarPathRenderer, PheromoneOverlay, PheromoneRenderer, PointRenderer, SearchFrontierOverlay, StartPoint, TemporaryLineRenderer, TemporaryLines};
+use crate::algorithm::flow_field::FlowField;
+use crate::algorithm::solve::a_star::SearchNode;
 use crate::game::algorithm_resource::AlgorithmResource;
+use crate::game::pathfinding_system::{ComparisonSolver, PathfindingStrategy};
 use bevy::prelude::*;
 
 pub fn render_start_goal(
@@ -47,16 +50,129 @@ pub fn render_start_goal(
     }
 }
 
+pub fn render_agent_radius(mut gizmos: Gizmos, algorithm_resource: Res<AlgorithmResource>) {
+    let radius = algorithm_resource.problem.agent_radius;
+    if radius <= 0.0 {
+        return;
+    }
+
+    let points = [
+        (algorithm_resource.problem.start, Color::srgb(0.0, 1.0, 0.0)),
+        (algorithm_resource.problem.goal, Color::srgb(1.0, 0.0, 0.0)),
+    ];
+    for (pos, color) in points {
+        if let Some(pos) = pos {
+            gizmos.circle_2d(Isometry2d::from_translation(pos), radius, color);
+        }
+    }
+}
+
+/// Waypoints as diamonds, joined in the order the route visits them.
+pub fn render_waypoints(
+    mut gizmos: Gizmos,
+    algorithm_resource: Res<AlgorithmResource>,
+    strategy_resource: Res<PathfindingStrategy>,
+) {
+    let waypoints = &algorithm_resource.problem.waypoints;
+    if waypoints.is_empty() {
+        return;
+    }
+
+    let color = Color::srgb(1.0, 0.85, 0.0);
+    for pos in waypoints {
+        let diamond = [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y, Vec2::X].map(|x| *pos + x * 12.0);
+        gizmos.linestrip_2d(diamond, color);
+    }
+    if let Some(route) = strategy_resource.cached_route() {
+        gizmos.linestrip_2d(route.iter().copied(), color.with_alpha(0.25));
+    }
+}
+
+/// Every tour on the hybrid's Pareto front, the chosen one brighter.
+pub fn render_pareto_front(mut gizmos: Gizmos, strategy_resource: Res<PathfindingStrategy>) {
+    let hybrid = &strategy_resource.hybrid_strategy;
+    if !hybrid.pareto {
+        return;
+    }
+
+    let tours = hybrid.pareto_front().tours();
+    let choice = hybrid.pareto_choice.min(tours.len().saturating_sub(1));
+    for (i, tour) in tours.iter().enumerate() {
+        let alpha = if i == choice { 0.9 } else { 0.25 };
+        gizmos.linestrip_2d(tour.path.iter().copied(), Color::srgb(0.6, 0.3, 1.0).with_alpha(alpha));
+    }
+}
+
+/// The tree(s) the RRT comparison solver grew on its last plan.
+pub fn render_rrt_tree(mut gizmos: Gizmos, strategy_resource: Res<PathfindingStrategy>) {
+    if !matches!(
+        strategy_resource.comparison,
+        ComparisonSolver::Rrt | ComparisonSolver::RrtConnect | ComparisonSolver::RrtStar
+    ) {
+        return;
+    }
+
+    let color = Color::srgba(1.0, 0.6, 0.2, 0.3);
+    for (from, to) in strategy_resource.comparison_strategies.rrt.tree_edges() {
+        gizmos.line_2d(*from, *to, color);
+    }
+}
+
+/// The graph kept between queries by the PRM, visibility graph or HPA* solver.
+pub fn render_roadmap(mut gizmos: Gizmos, strategy_resource: Res<PathfindingStrategy>) {
+    let strategies = &strategy_resource.comparison_strategies;
+    let edges = match strategy_resource.comparison {
+        ComparisonSolver::Prm => strategies.prm.roadmap_edges(),
+        ComparisonSolver::VisibilityGraph => strategies.visibility.graph_edges(),
+        ComparisonSolver::Hpa => {
+            for (min, max) in strategies.hpa.cluster_bounds() {
+                gizmos.rect_2d(Isometry2d::from_translation((min + max) / 2.0), max - min, Color::srgba(1.0, 0.6, 0.2, 0.4));
+            }
+            strategies.hpa.graph_edges()
+        }
+        _ => return,
+    };
+
+    let color = Color::srgba(1.0, 0.6, 0.2, 0.2);
+    for (from, to) in edges {
+        gizmos.line_2d(from, to, color);
+    }
+}
+
 pub fn render_path(
     mut commands: Commands,
     algorithm_resource: Res<AlgorithmResource>,
     path_query: Query<Entity, With<PathRenderer>>,
     astar_path_query: Query<Entity, With<AStarPathRenderer>>,
+    compare_path_query: Query<Entity, With<ComparePathRenderer>>,
 ) {
-    for entity in path_query.iter().chain(astar_path_query.iter()) {
+    for entity in path_query.iter().chain(astar_path_query.iter()).chain(compare_path_query.iter()) {
         commands.entity(entity).despawn();
     }
 
+    if let Some(path) = &algorithm_resource.compare_path {
+        for window in path.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let direction = end - start;
+
+            commands.spawn((
+                ComparePathRenderer,
+                Transform {
+                    translation: ((start + end) / 2.0).extend(0.45),
+                    rotation: Quat::from_rotation_z(f32::atan2(direction.y, direction.x)),
+                    scale: Vec3::new(direction.length(), 1.0, 1.0),
+                },
+                Visibility::default(),
+            )).with_children(|parent| {
+                parent.spawn(Sprite {
+                    color: Color::srgb(1.0, 0.55, 0.1),
+                    custom_size: Some(Vec2::new(1.0, 2.5)),
+                    ..default()
+                });
+            });
+        }
+    }
+
     if let Some(path) = &algorithm_resource.astar_path {
         if path.len() >= 2 {
             for i in 0..path.len() - 1 {
@@ -116,6 +232,275 @@ pub fn render_path(
     }
 }
 
+pub fn toggle_pheromone_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<PheromoneOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) {
+        overlay.visible = !overlay.visible;
+        println!("Pheromone overlay {}", if overlay.visible { "on" } else { "off" });
+    }
+}
+
+pub fn toggle_distance_field_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DistanceFieldOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyO) {
+        overlay.visible = !overlay.visible;
+        println!("Distance field overlay {}", if overlay.visible { "on" } else { "off" });
+    }
+}
+
+/// Shows the clearance, nearest wall and gradient under the cursor.
+pub fn render_distance_field(
+    mut gizmos: Gizmos,
+    overlay: Res<DistanceFieldOverlay>,
+    algorithm_resource: Res<AlgorithmResource>,
+    windows: Query<&Window>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let Some(cursor_screen_pos) = windows.single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some((camera, camera_transform)) = camera_q.iter().next() else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_screen_pos) else {
+        return;
+    };
+    let cursor = Vec2::new(ray.origin.x, ray.origin.y);
+
+    let field = algorithm_resource.grid.lock().unwrap().distance_field();
+    let Some(obstacle) = field.nearest_obstacle(cursor) else {
+        return;
+    };
+
+    let color = Color::srgb(0.3, 0.9, 1.0);
+    gizmos.circle_2d(Isometry2d::from_translation(cursor), field.distance_at(cursor), color.with_alpha(0.4));
+    gizmos.line_2d(cursor, obstacle, color);
+    gizmos.arrow_2d(cursor, cursor + field.gradient(cursor) * 30.0, Color::srgb(1.0, 0.9, 0.3));
+}
+
+pub fn toggle_flow_field_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    algorithm_resource: Res<AlgorithmResource>,
+    mut overlay: ResMut<FlowFieldOverlay>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Digit6) {
+        return;
+    }
+    overlay.visible = !overlay.visible;
+    println!("Flow field overlay {}", if overlay.visible { "on" } else { "off" });
+
+    let problem = &algorithm_resource.problem;
+    let (Some(start), Some(goal)) = (problem.start, problem.goal) else {
+        return;
+    };
+    if overlay.visible {
+        overlay.field = FlowField::from_grid(&algorithm_resource.grid.lock().unwrap(), goal);
+        match overlay.field.as_ref().and_then(|x| x.distance_at(start)) {
+            Some(distance) => println!(
+                "Start is {:.1} from the goal by the flow field (straight line {:.1})",
+                distance,
+                start.distance(goal)
+            ),
+            None => println!("The goal cannot be reached from the start"),
+        }
+    }
+}
+
+/// One arrow per reachable cell, coloured from near (green) to far (red), plus the streamline
+/// an agent dropped at the start would follow.
+pub fn render_flow_field(
+    mut gizmos: Gizmos,
+    mut overlay: ResMut<FlowFieldOverlay>,
+    algorithm_resource: Res<AlgorithmResource>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let Some(goal) = algorithm_resource.problem.goal else {
+        overlay.field = None;
+        return;
+    };
+
+    let grid = algorithm_resource.grid.lock().unwrap();
+    let stale = overlay
+        .field
+        .as_ref()
+        .is_none_or(|x| x.goal() != goal || x.revision() != grid.revision());
+    if stale {
+        overlay.field = FlowField::from_grid(&grid, goal);
+    }
+    let Some(field) = overlay.field.as_ref() else {
+        return;
+    };
+
+    let max_cost = field.max_cost().max(1.0);
+    let arrow_len = grid.cell_size() * 0.35;
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let Some(direction) = field.direction(x, y) else {
+                continue;
+            };
+            let center = grid.root() + (Vec2::new(x as f32, y as f32) + 0.5) * grid.cell_size();
+            let t = field.cost(x, y) / max_cost;
+            gizmos.arrow_2d(
+                center - direction * arrow_len,
+                center + direction * arrow_len,
+                Color::srgba(t, 1.0 - t, 0.2, 0.6),
+            );
+        }
+    }
+
+    let Some(start) = algorithm_resource.problem.start else {
+        return;
+    };
+    let step = grid.cell_size() * 0.25;
+    let mut streamline = vec![start];
+    let mut pos = start;
+    for _ in 0..grid.width() * grid.height() * 8 {
+        if pos.distance(goal) <= step {
+            streamline.push(goal);
+            break;
+        }
+        let Some(direction) = field.direction_at(pos) else {
+            break;
+        };
+        pos += direction * step;
+        streamline.push(pos);
+    }
+    gizmos.linestrip_2d(streamline, Color::srgb(0.3, 1.0, 0.6));
+}
+
+pub fn render_pheromones(
+    mut commands: Commands,
+    overlay: Res<PheromoneOverlay>,
+    strategy_resource: Res<PathfindingStrategy>,
+    pheromone_query: Query<Entity, With<PheromoneRenderer>>,
+) {
+    for entity in pheromone_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !overlay.visible {
+        return;
+    }
+
+    let edges = strategy_resource.hybrid_strategy.pheromone_edges();
+    if edges.is_empty() {
+        return;
+    }
+
+    let (min, max) = edges.iter().fold((f64::INFINITY, 0.0_f64), |(min, max), x| {
+        (min.min(x.2), max.max(x.2))
+    });
+
+    // Pheromone spans several orders of magnitude, so levels are compared on a log scale.
+    let log_min = min.max(f64::MIN_POSITIVE).ln();
+    let log_range = max.max(f64::MIN_POSITIVE).ln() - log_min;
+
+    for (start, end, pheromone) in edges {
+        let level = if log_range > 0.0 {
+            ((pheromone.max(f64::MIN_POSITIVE).ln() - log_min) / log_range) as f32
+        } else {
+            1.0
+        };
+
+        let direction = end - start;
+        let length = direction.length();
+        let angle = f32::atan2(direction.y, direction.x);
+
+        commands.spawn((
+            PheromoneRenderer,
+            Transform {
+                translation: ((start + end) / 2.0).extend(0.3),
+                rotation: Quat::from_rotation_z(angle),
+                scale: Vec3::new(length, 1.0, 1.0),
+            },
+            Visibility::default(),
+        )).with_children(|parent| {
+            parent.spawn(Sprite {
+                color: Color::srgba(1.0, level, 0.0, 0.2 + 0.8 * level),
+                custom_size: Some(Vec2::new(1.0, 1.0 + 4.0 * level)),
+                ..default()
+            });
+        });
+    }
+}
+
+pub fn toggle_search_frontier(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<SearchFrontierOverlay>,
+    mut strategy_resource: ResMut<PathfindingStrategy>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyF) {
+        overlay.visible = !overlay.visible;
+        overlay.progress = 0.0;
+        strategy_resource.astar_strategy.record_search = overlay.visible;
+        println!("A* search frontier {}", if overlay.visible { "on" } else { "off" });
+    }
+    if overlay.visible && keyboard_input.just_pressed(KeyCode::KeyG) {
+        overlay.color_by_g = !overlay.color_by_g;
+        println!("A* search frontier colored by {}", if overlay.color_by_g { "g" } else { "f" });
+    }
+}
+
+pub fn render_search_frontier(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut overlay: ResMut<SearchFrontierOverlay>,
+    strategy_resource: Res<PathfindingStrategy>,
+) {
+    if !overlay.visible {
+        return;
+    }
+
+    let astar = &strategy_resource.astar_strategy;
+    let Some(trace) = astar.search_trace() else {
+        return;
+    };
+
+    // Hold the finished search on screen for a moment before replaying the expansion.
+    overlay.progress += overlay.steps_per_second * time.delta_secs();
+    if overlay.progress > trace.expansions as f32 + overlay.steps_per_second {
+        overlay.progress = 0.0;
+    }
+    let step = overlay.progress as usize;
+
+    let value = |node: &SearchNode| {
+        if overlay.color_by_g { node.g } else { node.f }
+    };
+    let (min, max) = trace.nodes.iter().fold((f32::INFINITY, 0.0_f32), |(min, max), x| {
+        (min.min(value(x)), max.max(value(x)))
+    });
+
+    let cell = Vec2::splat(astar.step_size * 0.8);
+    for node in trace.nodes.iter() {
+        if node.opened_at > step {
+            continue;
+        }
+
+        let isometry = Isometry2d::from_translation(node.pos);
+        match node.closed_at {
+            Some(closed_at) if closed_at == step => {
+                gizmos.circle_2d(isometry, astar.step_size * 0.5, Color::srgb(1.0, 1.0, 0.0));
+            }
+            Some(closed_at) if closed_at < step => {
+                let level = if max > min { (value(node) - min) / (max - min) } else { 0.0 };
+                gizmos.rect_2d(isometry, cell, Color::srgb(level, 0.2, 1.0 - level));
+            }
+            _ => {
+                gizmos.circle_2d(isometry, astar.step_size * 0.2, Color::srgb(0.9, 0.9, 0.9));
+            }
+        }
+    }
+}
+
 pub fn render_temporary_lines(
     mut commands: Commands,
     mut query: Query<(Entity, &mut TemporaryLineRenderer)>,
@@ -178,14 +563,16 @@ pub fn clear_path(
     mut algorithm_resource: ResMut<AlgorithmResource>,
     path_query: Query<Entity, With<PathRenderer>>,
     astar_path_query: Query<Entity, With<AStarPathRenderer>>,
+    compare_path_query: Query<Entity, With<ComparePathRenderer>>,
     mut commands: Commands,
 ) {
     // Clear paths from algorithm resource
     algorithm_resource.path = None;
     algorithm_resource.astar_path = None;
+    algorithm_resource.compare_path = None;
 
     // Remove path entities
-    for entity in path_query.iter().chain(astar_path_query.iter()) {
+    for entity in path_query.iter().chain(astar_path_query.iter()).chain(compare_path_query.iter()) {
         commands.entity(entity).despawn();
     }
 }
@@ -5,6 +5,7 @@ use crate::{
     algorithm::{
         grid::{Grid, GridCell},
         problem::Problem,
+        waypoints::WaypointOrder,
     },
     game::{algorithm_resource::AlgorithmResource, control::GameState},
 };
@@ -25,9 +26,17 @@ pub fn create_algorithm_resource() -> AlgorithmResource {
             grid: _grid.clone(),
             start: None,
             goal: None,
+            agent_radius: 0.0,
+            agents: Vec::new(),
+            waypoints: Vec::new(),
+            waypoint_order: WaypointOrder::InOrder,
         },
         path: None,
         astar_path: None,
+        compare_path: None,
+        path_report: None,
+        astar_path_report: None,
+        compare_path_report: None,
     }
 }
 
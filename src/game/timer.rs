@@ -8,6 +8,9 @@ pub struct AlgorithmTimers {
     pub hybrid_last_ms: f64,
     pub hybrid_total_ms: f64,
     pub hybrid_max_ms: f64,
+    pub compare_last_ms: f64,
+    pub compare_total_ms: f64,
+    pub compare_max_ms: f64,
 }
 
 impl Default for AlgorithmTimers {
@@ -19,6 +22,9 @@ impl Default for AlgorithmTimers {
             hybrid_last_ms: 0.0,
             hybrid_total_ms: 0.0,
             hybrid_max_ms: 0.0,
+            compare_last_ms: 0.0,
+            compare_total_ms: 0.0,
+            compare_max_ms: 0.0,
         }
     }
 }
@@ -29,6 +35,8 @@ impl AlgorithmTimers {
         self.a_star_max_ms = 0.0;
         self.hybrid_total_ms = 0.0;
         self.hybrid_max_ms = 0.0;
+        self.compare_total_ms = 0.0;
+        self.compare_max_ms = 0.0;
     }
 
     pub fn log_timings(&self) {
@@ -42,4 +50,14 @@ impl AlgorithmTimers {
             self.hybrid_max_ms
         );
     }
+
+    pub fn log_compare_timings(&self, name: &str) {
+        println!(
+            "{}: last: {:.4} ms ; total: {:.4} ms ; max: {:.4} ms",
+            name,
+            self.compare_last_ms,
+            self.compare_total_ms,
+            self.compare_max_ms
+        );
+    }
 }